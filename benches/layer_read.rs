@@ -0,0 +1,116 @@
+//! Compares loading large layers with `HXAFile::from_bytes` against the per-element
+//! `read_exact` loop the layers used to be parsed with
+//!
+//! Run with `cargo bench --bench layer_read`
+
+use hxa::{HXAFile, HXAReader};
+use std::hint::black_box;
+use std::io::Read;
+use std::time::{Duration, Instant};
+
+const VERTEX_COUNT: u32 = 2_000_000;
+const ITERATIONS: u32 = 5;
+
+fn push_name(bytes: &mut Vec<u8>, name: &str) {
+    bytes.push(name.len() as u8);
+    bytes.extend_from_slice(name.as_bytes());
+}
+
+/// A point cloud with `vertex` and `normal` float layers and a `reference` layer of triangles
+fn large_mesh() -> Vec<u8> {
+    let corner_count = VERTEX_COUNT;
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(b"HxA\0");
+    bytes.push(3);
+    bytes.extend_from_slice(&1u32.to_le_bytes());
+
+    bytes.push(1);
+    bytes.extend_from_slice(&0u32.to_le_bytes());
+
+    bytes.extend_from_slice(&VERTEX_COUNT.to_le_bytes());
+    bytes.extend_from_slice(&2u32.to_le_bytes());
+    for name in ["vertex", "normal"] {
+        push_name(&mut bytes, name);
+        bytes.extend_from_slice(&[3, 2]);
+        for value in 0..VERTEX_COUNT * 3 {
+            bytes.extend_from_slice(&(value as f32).to_le_bytes());
+        }
+    }
+
+    bytes.extend_from_slice(&corner_count.to_le_bytes());
+    bytes.extend_from_slice(&1u32.to_le_bytes());
+    push_name(&mut bytes, "reference");
+    bytes.extend_from_slice(&[1, 1]);
+    for corner in 0..corner_count as i32 {
+        let reference = if corner % 3 == 2 { -corner - 1 } else { corner };
+        bytes.extend_from_slice(&reference.to_le_bytes());
+    }
+    bytes.extend_from_slice(&0u32.to_le_bytes());
+
+    bytes.extend_from_slice(&(corner_count / 3).to_le_bytes());
+    bytes.extend_from_slice(&0u32.to_le_bytes());
+    bytes
+}
+
+/// Reads every value with its own `read_exact` and `push`, like the original layer parser
+fn per_element_f32(input: &mut HXAReader<&[u8]>, count: usize) -> Vec<f32> {
+    let mut values = Vec::with_capacity(1);
+    for _ in 0..count {
+        let mut buffer = [0u8; 4];
+        input.read_exact(&mut buffer).expect("Expected enough data");
+        values.push(f32::from_le_bytes(buffer));
+    }
+    values
+}
+
+fn per_element_i32(input: &mut HXAReader<&[u8]>, count: usize) -> Vec<i32> {
+    let mut values = Vec::with_capacity(1);
+    for _ in 0..count {
+        let mut buffer = [0u8; 4];
+        input.read_exact(&mut buffer).expect("Expected enough data");
+        values.push(i32::from_le_bytes(buffer));
+    }
+    values
+}
+
+/// Walks the same file as `large_mesh` but decodes the layers one value at a time
+fn load_per_element(bytes: &[u8]) {
+    let mut input = HXAReader::new(bytes);
+    let skip = |input: &mut HXAReader<&[u8]>, length: usize| {
+        let mut buffer = vec![0u8; length];
+        input.read_exact(&mut buffer).expect("Expected enough data");
+    };
+
+    // header, node type, meta count, vertex count, layer count
+    skip(&mut input, 4 + 1 + 4 + 1 + 4 + 4 + 4);
+    for name in ["vertex", "normal"] {
+        skip(&mut input, 1 + name.len() + 2);
+        black_box(per_element_f32(&mut input, VERTEX_COUNT as usize * 3));
+    }
+
+    // corner count, layer count, layer header
+    skip(&mut input, 4 + 4 + 1 + "reference".len() + 2);
+    black_box(per_element_i32(&mut input, VERTEX_COUNT as usize));
+}
+
+fn time<F: FnMut()>(mut run: F) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        run();
+    }
+    start.elapsed() / ITERATIONS
+}
+
+fn main() {
+    let bytes = large_mesh();
+    println!("{} vertices, {} MiB", VERTEX_COUNT, bytes.len() / (1024 * 1024));
+
+    let per_element = time(|| load_per_element(black_box(&bytes)));
+    let bulk = time(|| {
+        black_box(HXAFile::from_bytes(black_box(&bytes)).expect("Expected a valid file"));
+    });
+
+    println!("per element read_exact: {:?}", per_element);
+    println!("bulk layer read:        {:?}", bulk);
+    println!("speedup:                {:.1}x", per_element.as_secs_f64() / bulk.as_secs_f64());
+}
@@ -0,0 +1,28 @@
+//! Feeds arbitrary bytes to the parsers, which must return an error rather than panic,
+//! overflow the stack or allocate far more than the input could describe
+//!
+//! Run with `cargo +nightly fuzz run parse`
+
+#![no_main]
+
+use hxa::{HXAFile, HXAFileView, LoadOptions};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let options = LoadOptions {
+        max_total_bytes: 16 * 1024 * 1024,
+        ..Default::default()
+    };
+
+    if let Ok(file) = HXAFile::from_bytes_with(data, options.clone()) {
+        // Anything that loads must be writable and load back the same way
+        let mut written = Vec::new();
+        file.write_to(&mut written).expect("Expected a loaded file to be writable");
+        HXAFile::from_bytes(&written).expect("Expected a written file to load");
+    }
+    let _ = HXAFileView::from_bytes_with(data, options.clone());
+
+    // Readers of unknown length must not trust the sizes in the file even without a limit
+    let _ = HXAFile::from_reader_with(data, options);
+    let _ = HXAFile::from_reader(data);
+});
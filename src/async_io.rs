@@ -0,0 +1,58 @@
+use crate::error::{HXAError, reject_skipped_nodes};
+use crate::hxfile::HXAFile;
+use crate::options::LoadOptions;
+use crate::parser::HXAParser;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// Size of the pieces read from the `AsyncRead` before they are fed to the parser
+const CHUNK_SIZE: usize = 64 * 1024;
+
+impl HXAFile {
+    /// Loads a HxA file from a tokio `AsyncRead`, such as a `tokio::fs::File` or a socket
+    ///
+    /// The input is fed to a `HXAParser` as it arrives, so only the node being received is buffered.
+    /// Each node is decoded on the calling task once all of its bytes are in, which blocks the task about as long
+    /// as copying the node. For very large nodes, load with `HXAFile::from_reader` inside `tokio::task::spawn_blocking`
+    pub async fn load_async<R: AsyncRead + Unpin>(reader: R) -> Result<HXAFile, HXAError> {
+        HXAFile::load_async_with(reader, LoadOptions::default()).await
+    }
+
+    /// Loads a HxA file from a tokio `AsyncRead` under the given limits
+    ///
+    /// The limits apply as they do for `HXAParser`, see `HXAParser::feed`
+    pub async fn load_async_with<R: AsyncRead + Unpin>(mut reader: R, options: LoadOptions) -> Result<HXAFile, HXAError> {
+        let mut parser = HXAParser::with_options(options);
+        let mut new_hxa_file = HXAFile::new();
+        let mut chunk = vec![0u8; CHUNK_SIZE];
+
+        while !parser.is_complete() {
+            let read = reader.read(&mut chunk).await?;
+            if read == 0 {
+                break;
+            }
+            new_hxa_file.node_array.extend(parser.feed(&chunk[..read])?);
+        }
+
+        if let Some(version) = parser.version() {
+            new_hxa_file.version = version;
+        }
+        // The caller gets no warnings, so an unknown node that hid the nodes after it has to fail the load
+        reject_skipped_nodes(&parser.take_warnings())?;
+        new_hxa_file.node_array.extend(parser.finish()?);
+        new_hxa_file.node_count = new_hxa_file.node_array.len() as u32;
+
+        Ok(new_hxa_file)
+    }
+
+    /// Writes the file in the binary HxA format to a tokio `AsyncWrite`
+    ///
+    /// The file is encoded in memory first as `write_to` does, then written without blocking
+    pub async fn save_async<W: AsyncWrite + Unpin>(&self, mut output: W) -> Result<(), HXAError> {
+        let mut bytes = Vec::new();
+        self.write_to(&mut bytes)?;
+
+        output.write_all(&bytes).await?;
+        output.flush().await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,194 @@
+use crate::enums::HXALayerDataType;
+use crate::error::HXAError;
+use crate::layer::HXALayer;
+use crate::node::HXAGeometryNode;
+use crate::schema::HXAStack;
+use crate::triangulate::HXATriangulation;
+use std::collections::HashMap;
+
+/// One attribute interleaved in a `HXAVertexBuffer`
+#[derive(Debug, Clone)]
+pub struct HXAVertexAttribute {
+    pub name: String,
+    pub components: u8,
+
+    /// Position of the first component within a vertex, in floats
+    pub offset: usize,
+
+    /// The stack the layer was found in
+    pub stack: HXAStack,
+}
+
+/// Interleaved vertices and triangle indices ready to upload to a GPU
+#[derive(Debug, Clone)]
+pub struct HXAVertexBuffer {
+    /// The attributes of every vertex one after the other, `stride` floats per vertex
+    pub vertices: Vec<f32>,
+
+    /// Three indices into the vertices per triangle
+    pub indices: Vec<u32>,
+
+    /// Number of floats per vertex
+    pub stride: usize,
+
+    pub attributes: Vec<HXAVertexAttribute>,
+}
+
+impl HXAVertexBuffer {
+    /// Number of unique vertices
+    pub fn vertex_count(&self) -> usize {
+        match self.stride {
+            0 => 0,
+            stride => self.vertices.len() / stride,
+        }
+    }
+}
+
+/// Builds a `HXAVertexBuffer` from a geometry node
+///
+/// Each layer is taken from the `corner_stack` if it holds a layer with that name, otherwise from the
+/// `vertex_stack` and lastly the `face_stack`. Every triangle corner gets the values of all layers,
+/// and corners with identical values share a single vertex. Values are converted to `f32` as they are,
+/// `UINT8` values are not normalized.
+/// # Example
+/// ```rust,no_run
+/// let my_hxa = hxa::HXAFile::from("Cube.hxa");
+/// let geometry = my_hxa.get_first_geometry().expect("Expected to find a geometry node").0;
+///
+/// let buffer = hxa::HXAVertexBufferBuilder::new()
+///     .layer("vertex")
+///     .layer("normal")
+///     .layer("uv")
+///     .build(geometry)
+///     .expect("Expected the layers to exist");
+/// ```
+#[derive(Debug, Clone)]
+pub struct HXAVertexBufferBuilder {
+    layers: Vec<String>,
+    triangulation: HXATriangulation,
+}
+
+impl HXAVertexBufferBuilder {
+    /// A builder without layers that triangulates with `HXATriangulation::EarClipping`
+    pub fn new() -> Self {
+        HXAVertexBufferBuilder {
+            layers: Vec::new(),
+            triangulation: HXATriangulation::EarClipping,
+        }
+    }
+
+    /// Adds the layer named `name` as the next attribute of each vertex
+    pub fn layer(mut self, name: &str) -> Self {
+        self.layers.push(String::from(name));
+        self
+    }
+
+    /// Chooses how polygons are split into triangles
+    pub fn triangulation(mut self, triangulation: HXATriangulation) -> Self {
+        self.triangulation = triangulation;
+        self
+    }
+
+    pub fn build(&self, geometry: &HXAGeometryNode) -> Result<HXAVertexBuffer, HXAError> {
+        let triangles = geometry.try_triangulate(self.triangulation).ok_or_else(|| geometry.missing_layer())?;
+
+        let mut sources = Vec::with_capacity(self.layers.len());
+        let mut attributes = Vec::with_capacity(self.layers.len());
+        let mut stride = 0;
+        for name in &self.layers {
+            let (stack, layer, num_items) = if let Some(layer) = geometry.corner_stack.find(name) {
+                (HXAStack::Corner, layer, geometry.edge_corner_count)
+            } else if let Some(layer) = geometry.vertex_stack.find(name) {
+                (HXAStack::Vertex, layer, geometry.vertex_count)
+            } else if let Some(layer) = geometry.face_stack.find(name) {
+                (HXAStack::Face, layer, geometry.face_count)
+            } else {
+                return Err(HXAError::MissingLayer(name.clone()));
+            };
+
+            check_layer(layer, num_items)?;
+            attributes.push(HXAVertexAttribute {
+                name: name.clone(),
+                components: layer.components,
+                offset: stride,
+                stack,
+            });
+            sources.push(layer);
+            stride += layer.components as usize;
+        }
+
+        let mut vertices = Vec::new();
+        let mut indices = Vec::with_capacity(triangles.indices.len());
+        let mut unique: HashMap<Vec<u32>, u32> = HashMap::new();
+        let mut values = Vec::with_capacity(stride);
+
+        for (entry, vertex) in triangles.indices.iter().enumerate() {
+            let corner = triangles.triangle_corners[entry] as usize;
+            let face = triangles.triangle_faces[entry / 3] as usize;
+
+            values.clear();
+            for (layer, attribute) in sources.iter().zip(&attributes) {
+                let item = match attribute.stack {
+                    HXAStack::Vertex => *vertex as usize,
+                    HXAStack::Corner => corner,
+                    _ => face,
+                };
+                push_values(&mut values, layer, item)?;
+            }
+
+            // Compare the bits so the values can be hashed, identical corners always have identical bits
+            let key: Vec<u32> = values.iter().map(|value| value.to_bits()).collect();
+            let next_index = unique.len() as u32;
+            let index = *unique.entry(key).or_insert_with(|| {
+                vertices.extend_from_slice(&values);
+                next_index
+            });
+            indices.push(index);
+        }
+
+        Ok(HXAVertexBuffer {
+            vertices,
+            indices,
+            stride,
+            attributes,
+        })
+    }
+}
+
+impl Default for HXAVertexBufferBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn check_layer(layer: &HXALayer, num_items: u32) -> Result<(), HXAError> {
+    let expected = num_items as usize * layer.components as usize;
+    if layer.layer_type.len() != expected {
+        return Err(HXAError::InvalidLayer {
+            layer: layer.name.clone(),
+            reason: format!("expected {} values but it holds {}", expected, layer.layer_type.len()),
+        });
+    }
+    Ok(())
+}
+
+/// Appends the components of `item` in the layer as floats
+fn push_values(values: &mut Vec<f32>, layer: &HXALayer, item: usize) -> Result<(), HXAError> {
+    let components = layer.components as usize;
+    let range = item * components..(item + 1) * components;
+
+    // Indices come from the reference layer, which may point past the end of a vertex layer
+    let out_of_range = || HXAError::InvalidLayer {
+        layer: layer.name.clone(),
+        reason: format!("item {} is out of range", item),
+    };
+
+    match &layer.layer_type {
+        HXALayerDataType::UINT8(data) => values.extend(data.get(range).ok_or_else(out_of_range)?.iter().map(|value| *value as f32)),
+        HXALayerDataType::INT32(data) => values.extend(data.get(range).ok_or_else(out_of_range)?.iter().map(|value| *value as f32)),
+        HXALayerDataType::FLOAT(data) => values.extend_from_slice(data.get(range).ok_or_else(out_of_range)?),
+        HXALayerDataType::DOUBLE(data) => values.extend(data.get(range).ok_or_else(out_of_range)?.iter().map(|value| *value as f32)),
+        HXALayerDataType::Unknown => return Err(HXAError::InvalidLayer { layer: layer.name.clone(), reason: String::from("unknown layer type") }),
+    }
+    Ok(())
+}
@@ -1,4 +1,4 @@
-/// 
+/// Hard conventions which every valid file follows
 pub mod hard {
     /// Despite being called 'vertex' this layer contains just the vertex positions of a mesh   
     /// ### Note
@@ -1,5 +1,5 @@
 use crate::meta::HXAMeta; //how are circular imports even allowed
-use crate::node::HXAGeometryNode; //how are circular imports even allowed
+use crate::node::{HXAGeometryNode, HXAImageNode}; //how are circular imports even allowed
 
 #[derive(Debug)]
 pub enum HXAMetaDataType{
@@ -16,7 +16,11 @@ pub enum HXAMetaDataType{
     },
 
 
-	NODE,
+    /// # Assumption
+    /// `data_length` is the number of node indices to read
+	NODE {
+        node_array: Vec<u32>
+    },
 
     /// # Assumption
     /// `data_length` is the number of characters in the text
@@ -25,7 +29,11 @@ pub enum HXAMetaDataType{
     },
 
 
-	BINARY,
+    /// # Assumption
+    /// `data_length` is the number of bytes to read
+	BINARY {
+        binary_array: Vec<u8>
+    },
 
     /// # Assumption
     /// Contains more meta data.
@@ -52,7 +60,9 @@ impl From<u8> for HXAMetaDataType{
                 }
             },
             2u8 => {
-                HXAMetaDataType::NODE
+                HXAMetaDataType::NODE {
+                    node_array: Vec::with_capacity(1)
+                }
             },
             3u8 => {
                 HXAMetaDataType::TEXT {
@@ -60,7 +70,9 @@ impl From<u8> for HXAMetaDataType{
                 }
             },
             4u8 => {
-                HXAMetaDataType::BINARY
+                HXAMetaDataType::BINARY {
+                    binary_array: Vec::with_capacity(1)
+                }
             },
             5u8 => {
                 HXAMetaDataType::META{
@@ -75,6 +87,21 @@ impl From<u8> for HXAMetaDataType{
     }
 }
 
+impl HXAMetaDataType {
+    /// The byte used for this type in the file, `None` for `COUNT` and `Unknown`
+    pub fn type_code(&self) -> Option<u8> {
+        match self {
+            HXAMetaDataType::INT64 { .. } => Some(0),
+            HXAMetaDataType::DOUBLE { .. } => Some(1),
+            HXAMetaDataType::NODE { .. } => Some(2),
+            HXAMetaDataType::TEXT { .. } => Some(3),
+            HXAMetaDataType::BINARY { .. } => Some(4),
+            HXAMetaDataType::META { .. } => Some(5),
+            HXAMetaDataType::COUNT | HXAMetaDataType::Unknown => None,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum HXANodeType {
     MetaOnly, // node only containing meta data.
@@ -83,10 +110,18 @@ pub enum HXANodeType {
 	Geometry(HXAGeometryNode),
 
     /// node containing a 1D, 2D, 3D, or Cube image, and meta data.
-	Image, 
+	Image(HXAImageNode), 
 
     /// If the file designates the node type as anything other than `Meta`, `Geomatry` or`Image`
 	Unknown, 
+
+    /// A node type from a newer exporter, kept as raw bytes when `LoadOptions::keep_unknown_nodes` is set
+    /// 
+    /// The file does not store the length of a node, so only a final node can be kept, its payload being the rest of the file
+    Unsupported {
+        type_code: u8,
+        payload: Vec<u8>
+    },
 }
 
 impl From<u8> for HXANodeType{
@@ -99,13 +134,90 @@ impl From<u8> for HXANodeType{
                 HXANodeType::Geometry(HXAGeometryNode::new())
             },
             2u8 => {
-                HXANodeType::Image
+                HXANodeType::Image(HXAImageNode::new())
             },
             _ => HXANodeType::Unknown
         }
     }
 }
 
+impl HXANodeType {
+    /// The byte used for this type in the file, `None` for `Unknown`
+    pub fn type_code(&self) -> Option<u8> {
+        match self {
+            HXANodeType::MetaOnly => Some(0),
+            HXANodeType::Geometry(_) => Some(1),
+            HXANodeType::Image(_) => Some(2),
+            HXANodeType::Unsupported { type_code, .. } => Some(*type_code),
+            HXANodeType::Unknown => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum HXAImageType {
+    /// 6 sided cube, in the order of: +x, -x, +y, -y, +z, -z.
+    Cube,
+
+    /// One dimensional pixel data.
+    Image1D,
+
+    /// Two dimensional pixel data.
+    Image2D,
+
+    /// Three dimensional pixel data.
+    Image3D,
+
+    /// If the file designates the image type as anything other than `Cube`, `Image1D`, `Image2D` or `Image3D`
+    Unknown,
+}
+
+impl From<u8> for HXAImageType{
+    fn from(value: u8) -> Self {
+        match value {
+            0u8 => HXAImageType::Cube,
+            1u8 => HXAImageType::Image1D,
+            2u8 => HXAImageType::Image2D,
+            3u8 => HXAImageType::Image3D,
+            _ => HXAImageType::Unknown
+        }
+    }
+}
+
+impl HXAImageType {
+    /// The byte used for this type in the file, `None` for `Unknown`
+    pub fn type_code(&self) -> Option<u8> {
+        match self {
+            HXAImageType::Cube => Some(0),
+            HXAImageType::Image1D => Some(1),
+            HXAImageType::Image2D => Some(2),
+            HXAImageType::Image3D => Some(3),
+            HXAImageType::Unknown => None,
+        }
+    }
+
+    /// Number of resolution values stored in the file, each cube side is a 2D image
+    pub fn dimensions(&self) -> usize {
+        match self {
+            HXAImageType::Cube => 2,
+            HXAImageType::Image1D => 1,
+            HXAImageType::Image2D => 2,
+            HXAImageType::Image3D => 3,
+            HXAImageType::Unknown => 0,
+        }
+    }
+
+    /// Number of pixels in an image of this type, including all 6 sides of a cube image
+    pub fn pixel_count(&self, resolution: &[u32; 3]) -> u32 {
+        // A saturated count can never be satisfied by the remaining data, so reading it fails as truncated
+        let pixels = resolution.iter().fold(1u32, |total, side| total.saturating_mul(*side));
+        match self {
+            HXAImageType::Cube => pixels.saturating_mul(6),
+            _ => pixels,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum  HXALayerDataType{
     UINT8 (Vec<u8>),
@@ -135,3 +247,87 @@ impl From<u8> for HXALayerDataType{
     }
 }
 
+impl HXALayerDataType {
+    /// The byte used for this type in the file, `None` for `Unknown`
+    pub fn type_code(&self) -> Option<u8> {
+        match self {
+            HXALayerDataType::UINT8(_) => Some(0),
+            HXALayerDataType::INT32(_) => Some(1),
+            HXALayerDataType::FLOAT(_) => Some(2),
+            HXALayerDataType::DOUBLE(_) => Some(3),
+            HXALayerDataType::Unknown => None,
+        }
+    }
+
+    /// Number of values stored, regardless of the type
+    pub fn len(&self) -> usize {
+        match self {
+            HXALayerDataType::UINT8(data) => data.len(),
+            HXALayerDataType::INT32(data) => data.len(),
+            HXALayerDataType::FLOAT(data) => data.len(),
+            HXALayerDataType::DOUBLE(data) => data.len(),
+            HXALayerDataType::Unknown => 0,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of bytes each value takes in the file, `None` for `Unknown`
+    pub fn value_size(&self) -> Option<usize> {
+        self.value_type().map(|value_type| value_type.size())
+    }
+
+    /// The type of the values without the values, `None` for `Unknown`
+    pub fn value_type(&self) -> Option<HXALayerValueType> {
+        match self {
+            HXALayerDataType::UINT8(_) => Some(HXALayerValueType::UINT8),
+            HXALayerDataType::INT32(_) => Some(HXALayerValueType::INT32),
+            HXALayerDataType::FLOAT(_) => Some(HXALayerValueType::FLOAT),
+            HXALayerDataType::DOUBLE(_) => Some(HXALayerValueType::DOUBLE),
+            HXALayerDataType::Unknown => None,
+        }
+    }
+}
+
+/// The type of the values in a layer, like `HXALayerDataType` without the data
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HXALayerValueType {
+    UINT8,
+    INT32,
+    FLOAT,
+    DOUBLE,
+}
+
+impl HXALayerValueType {
+    /// The type stored as `type_code` in the file, `None` if it is not a known type
+    pub fn from_type_code(type_code: u8) -> Option<Self> {
+        match type_code {
+            0 => Some(HXALayerValueType::UINT8),
+            1 => Some(HXALayerValueType::INT32),
+            2 => Some(HXALayerValueType::FLOAT),
+            3 => Some(HXALayerValueType::DOUBLE),
+            _ => None,
+        }
+    }
+
+    /// The byte used for this type in the file
+    pub fn type_code(&self) -> u8 {
+        match self {
+            HXALayerValueType::UINT8 => 0,
+            HXALayerValueType::INT32 => 1,
+            HXALayerValueType::FLOAT => 2,
+            HXALayerValueType::DOUBLE => 3,
+        }
+    }
+
+    /// Number of bytes each value takes in the file
+    pub fn size(&self) -> usize {
+        match self {
+            HXALayerValueType::UINT8 => 1,
+            HXALayerValueType::INT32 | HXALayerValueType::FLOAT => 4,
+            HXALayerValueType::DOUBLE => 8,
+        }
+    }
+}
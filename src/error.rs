@@ -0,0 +1,253 @@
+use crate::hxfile::HXA_SUPPORTED_VERSIONS;
+use std::fmt;
+use std::io;
+use std::str::Utf8Error;
+
+/// Everything that can go wrong while loading, saving or using a HxA file
+#[derive(Debug)]
+pub enum HXAError {
+    /// The underlying reader failed for a reason other than running out of data
+    Io(io::Error),
+
+    /// The input ended before the structure being read was complete
+    Truncated,
+
+    /// A name or text field did not contain valid utf8
+    InvalidUtf8(Utf8Error),
+
+    /// The node type byte was not one of `Meta`, `Geometry` or `Image`
+    UnknownNodeType(u8),
+
+    /// The layer type byte was not one of the `HXALayerDataType`s
+    UnknownLayerType(u8),
+
+    /// The image type byte was not one of the `HXAImageType`s
+    UnknownImageType(u8),
+
+    /// The meta data type byte was not one of the `HXAMetaDataType`s
+    UnknownMetaType(u8),
+
+    /// The file did not begin with the `HxA` magic number
+    BadMagic(u32),
+
+    /// The file was written with a format version outside of `HXA_SUPPORTED_VERSIONS`
+    UnsupportedVersion(u8),
+
+    /// A count or size in the file went over one of the `LoadOptions` limits
+    LimitExceeded {
+        /// Name of the `LoadOptions` field that was exceeded
+        limit: &'static str,
+        value: u64,
+        max: u64,
+    },
+
+    /// The structure in memory cannot be represented in the HxA format, for example a name longer than 255 bytes
+    Unwritable(String),
+
+    /// A `HXAObserver` asked for the load or save to stop
+    Cancelled,
+
+    /// A node was asked for by an index past the last node
+    NoSuchNode {
+        node_index: usize,
+        node_count: usize,
+    },
+
+    /// A layer needed to build something from a geometry node is not in any of its stacks
+    MissingLayer(String),
+
+    /// A layer cannot be used because of its type, components or length
+    InvalidLayer {
+        layer: String,
+        reason: String,
+    },
+
+    /// Another error annotated with where in the file it happened
+    Located {
+        /// Byte offset from the start of the file, `None` when writing
+        offset: Option<u64>,
+
+        /// Breadcrumb to the structure being read, such as `node[2].geometry.corner_stack.layer["uv"]`
+        path: String,
+
+        error: Box<HXAError>,
+    },
+}
+
+impl HXAError {
+    /// Records the byte offset where the error happened, keeping the innermost offset if one is already set
+    pub(crate) fn at(self, offset: u64) -> Self {
+        match self {
+            HXAError::Located { .. } => self,
+            error => HXAError::Located {
+                offset: Some(offset),
+                path: String::new(),
+                error: Box::new(error),
+            },
+        }
+    }
+
+    /// Prepends a segment such as `node[2]` to the breadcrumb path
+    pub(crate) fn within<S: AsRef<str>>(self, segment: S) -> Self {
+        match self {
+            HXAError::Located { offset, path, error } => HXAError::Located {
+                offset,
+                path: if path.is_empty() {
+                    String::from(segment.as_ref())
+                } else {
+                    format!("{}.{}", segment.as_ref(), path)
+                },
+                error,
+            },
+            error => HXAError::Located {
+                offset: None,
+                path: String::from(segment.as_ref()),
+                error: Box::new(error),
+            }
+        }
+    }
+
+    /// The byte offset in the file where parsing failed, if known
+    pub fn offset(&self) -> Option<u64> {
+        match self {
+            HXAError::Located { offset, .. } => *offset,
+            _ => None,
+        }
+    }
+
+    /// The breadcrumb to the structure that failed to parse, for example `node[2].geometry.corner_stack.layer["uv"]`
+    pub fn path(&self) -> Option<&str> {
+        match self {
+            HXAError::Located { path, .. } => Some(path),
+            _ => None,
+        }
+    }
+
+    /// The underlying error without the location information
+    pub fn root(&self) -> &HXAError {
+        match self {
+            HXAError::Located { error, .. } => error.root(),
+            error => error,
+        }
+    }
+}
+
+impl fmt::Display for HXAError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HXAError::Io(err) => write!(f, "io error: {}", err),
+            HXAError::Truncated => write!(f, "unexpected end of data"),
+            HXAError::InvalidUtf8(err) => write!(f, "invalid utf8: {}", err),
+            HXAError::UnknownNodeType(value) => write!(f, "unknown node type {}", value),
+            HXAError::UnknownLayerType(value) => write!(f, "unknown layer type {}", value),
+            HXAError::UnknownImageType(value) => write!(f, "unknown image type {}", value),
+            HXAError::UnknownMetaType(value) => write!(f, "unknown meta data type {}", value),
+            HXAError::BadMagic(value) => write!(f, "bad magic number {:#010X} (not a HxA file)", value),
+            HXAError::UnsupportedVersion(value) => write!(
+                f,
+                "unsupported format version {} (versions {} to {} are supported)",
+                value,
+                HXA_SUPPORTED_VERSIONS.start(),
+                HXA_SUPPORTED_VERSIONS.end()
+            ),
+            HXAError::LimitExceeded { limit, value, max } => write!(f, "{} of {} is over the limit of {}", limit, value, max),
+            HXAError::Unwritable(reason) => write!(f, "cannot write as HxA: {}", reason),
+            HXAError::Cancelled => write!(f, "cancelled"),
+            HXAError::NoSuchNode { node_index, node_count } => write!(f, "no node at index {}, there are {} nodes", node_index, node_count),
+            HXAError::MissingLayer(layer) => write!(f, "no layer named {:?}", layer),
+            HXAError::InvalidLayer { layer, reason } => write!(f, "layer {:?} cannot be used: {}", layer, reason),
+            HXAError::Located { offset, path, error } => {
+                write!(f, "{}", error)?;
+                if let Some(offset) = offset {
+                    write!(f, " at byte {:#X}", offset)?;
+                }
+                if !path.is_empty() {
+                    write!(f, " in {}", path)?;
+                }
+                Ok(())
+            },
+        }
+    }
+}
+
+impl std::error::Error for HXAError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            HXAError::Io(err) => Some(err),
+            HXAError::InvalidUtf8(err) => Some(err),
+            HXAError::Located { error, .. } => error.source(),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for HXAError {
+    fn from(value: io::Error) -> Self {
+        match value.kind() {
+            io::ErrorKind::UnexpectedEof => HXAError::Truncated,
+            _ => HXAError::Io(value),
+        }
+    }
+}
+
+impl From<Utf8Error> for HXAError {
+    fn from(value: Utf8Error) -> Self {
+        HXAError::InvalidUtf8(value)
+    }
+}
+
+/// Something unusual that was tolerated while loading
+#[derive(Debug)]
+pub enum HXAWarning {
+    /// The final node has a type this crate does not know, its bytes were kept as they are
+    UnknownNodeKept {
+        node_index: u32,
+        type_code: u8,
+
+        /// Byte offset of the node from the start of the file
+        offset: u64,
+
+        /// Number of bytes in the payload after the meta data
+        payload_length: usize,
+    },
+
+    /// A node of unknown type was followed by other nodes, as its length cannot be known it was dropped
+    /// and loading stopped before it
+    UnknownNodeSkipped {
+        node_index: u32,
+        type_code: u8,
+
+        /// Byte offset of the node from the start of the file
+        offset: u64,
+
+        /// Number of nodes after it that could not be located
+        nodes_after: u32,
+    },
+}
+
+impl fmt::Display for HXAWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HXAWarning::UnknownNodeKept { node_index, type_code, offset, payload_length } => write!(
+                f,
+                "node[{}] at byte {:#X} has unknown type {}, its {} byte payload was kept unchanged",
+                node_index, offset, type_code, payload_length
+            ),
+            HXAWarning::UnknownNodeSkipped { node_index, type_code, offset, nodes_after } => write!(
+                f,
+                "node[{}] at byte {:#X} has unknown type {}, it and the {} nodes after it were skipped",
+                node_index, offset, type_code, nodes_after
+            ),
+        }
+    }
+}
+
+/// Fails like a load without `keep_unknown_nodes` if nodes were skipped, for entry points that do not return warnings
+pub(crate) fn reject_skipped_nodes(warnings: &[HXAWarning]) -> Result<(), HXAError> {
+    for warning in warnings {
+        if let HXAWarning::UnknownNodeSkipped { node_index, type_code, offset, .. } = warning {
+            return Err(HXAError::UnknownNodeType(*type_code).at(*offset).within(format!("node[{}]", node_index)));
+        }
+    }
+    Ok(())
+}
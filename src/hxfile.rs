@@ -1,9 +1,25 @@
+use crate::enums::HXANodeType;
+use crate::error::{HXAError, HXAWarning, reject_skipped_nodes};
 use crate::meta::HXAMeta;
 use crate::node::{HXANode, HXAGeometryNode};
-use crate::macros::{buffer,read_bytes};
+use crate::options::LoadOptions;
+use crate::progress::{CountingWriter, HXAObserver, HXAProgress};
+use crate::macros::{buffer,read_bytes,whereami,write_bytes,write_len};
+use crate::reader::HXAReader;
+use crate::summary::HXAFileSummary;
 use std::fs::File;
-use std::io::{BufReader, Read};
-use std::str;
+use std::io::{BufReader, BufWriter, Cursor, Read, Write};
+use std::ops::RangeInclusive;
+use std::path::Path;
+
+/// The 4 bytes `"HxA\0"` every file begins with, read as a little endian `u32`
+pub const HXA_MAGIC_NUMBER: u32 = u32::from_le_bytes(*b"HxA\0");
+
+/// The format version written by this crate
+pub const HXA_VERSION_FORMAT: u8 = 3;
+
+/// The format versions this crate can read, files from newer exporters are rejected
+pub const HXA_SUPPORTED_VERSIONS: RangeInclusive<u8> = 1..=HXA_VERSION_FORMAT;
 
 #[derive(Debug)]
 pub struct HXAFile{
@@ -17,28 +33,242 @@ pub struct HXAFile{
 impl HXAFile {
     pub fn new() -> Self{
         HXAFile{
-            magic_number: 0,
-            version: 0,
+            magic_number: HXA_MAGIC_NUMBER,
+            version: HXA_VERSION_FORMAT,
             node_count: 0,
             node_array: Vec::with_capacity(1),
         }
     }
 
-    pub fn read_header(self: &mut HXAFile, input: &mut BufReader<File>){
-        //Read magic number
-        self.magic_number = read_bytes!{input u32};
-        
-        //Read version number
-        self.version = read_bytes!{input u8};
+    /// Opens and parses the HxA file at `path`
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<HXAFile, HXAError> {
+        HXAFile::open_with(path, LoadOptions::default())
+    }
+
+    /// Opens and parses the HxA file at `path` under the given limits
+    pub fn open_with<P: AsRef<Path>>(path: P, options: LoadOptions) -> Result<HXAFile, HXAError> {
+        let file = File::open(path)?;
+        let length = file.metadata()?.len();
+
+        let mut input = HXAReader::with_options(BufReader::new(file), options, Some(length));
+        HXAFile::from_hxa_reader_strict(&mut input)
+    }
+
+    /// Parses a HxA file from any `Read` source, such as an archive entry or a network stream
+    /// 
+    /// The reader is read in many small pieces, so wrap unbuffered sources like `File` in a `BufReader`
+    pub fn from_reader<R: Read>(reader: R) -> Result<HXAFile, HXAError> {
+        HXAFile::from_reader_with(reader, LoadOptions::default())
+    }
+
+    /// Parses a HxA file from any `Read` source under the given limits
+    /// 
+    /// The length of a reader is not known, so payloads are read as they arrive rather than allocated up front.
+    /// Set `max_total_bytes` when the source is not trusted, to bound how much a long stream can make it keep
+    pub fn from_reader_with<R: Read>(reader: R, options: LoadOptions) -> Result<HXAFile, HXAError> {
+        let mut input = HXAReader::with_options(reader, options, None);
+        HXAFile::from_hxa_reader_strict(&mut input)
+    }
+
+    /// Opens and parses the HxA file at `path`, reporting progress to `observer` which can cancel the load
+    pub fn open_observed<P: AsRef<Path>, O: HXAObserver + 'static>(path: P, options: LoadOptions, observer: O) -> Result<HXAFile, HXAError> {
+        let file = File::open(path)?;
+        let length = file.metadata()?.len();
+
+        let mut input = HXAReader::with_options(BufReader::new(file), options, Some(length));
+        input.set_observer(observer);
+        HXAFile::from_hxa_reader_strict(&mut input)
+    }
+
+    /// Opens and parses the HxA file at `path`, keeping nodes of unknown type instead of failing
+    /// 
+    /// See `LoadOptions::keep_unknown_nodes` for what can be kept
+    pub fn open_lenient<P: AsRef<Path>>(path: P) -> Result<(HXAFile, Vec<HXAWarning>), HXAError> {
+        let file = File::open(path)?;
+        let length = file.metadata()?.len();
+
+        let options = LoadOptions { keep_unknown_nodes: true, ..Default::default() };
+        let mut input = HXAReader::with_options(BufReader::new(file), options, Some(length));
+        let new_hxa_file = HXAFile::from_hxa_reader(&mut input)?;
+
+        Ok((new_hxa_file, input.take_warnings()))
+    }
+
+    /// Parses a HxA file that is already in memory, keeping nodes of unknown type instead of failing
+    /// 
+    /// See `LoadOptions::keep_unknown_nodes` for what can be kept
+    pub fn from_bytes_lenient(bytes: &[u8]) -> Result<(HXAFile, Vec<HXAWarning>), HXAError> {
+        let options = LoadOptions { keep_unknown_nodes: true, ..Default::default() };
+        let mut input = HXAReader::with_options(bytes, options, Some(bytes.len() as u64));
+        let new_hxa_file = HXAFile::from_hxa_reader(&mut input)?;
+
+        Ok((new_hxa_file, input.take_warnings()))
+    }
+
+    /// Parses a HxA file from a reader that already carries its `LoadOptions`
+    /// 
+    /// Any warnings are left in the reader
+    pub fn from_hxa_reader<R: Read>(input: &mut HXAReader<R>) -> Result<HXAFile, HXAError> {
+        let mut new_hxa_file = HXAFile::new();
+        new_hxa_file.read_header(input)?;
+
+        Ok(new_hxa_file)
+    }
+
+    /// Parses like `from_hxa_reader` for entry points that return no warnings
+    ///
+    /// The caller could not tell that nodes were left out, so an unknown node followed by other nodes
+    /// fails with `HXAError::UnknownNodeType` even when `keep_unknown_nodes` is set
+    fn from_hxa_reader_strict<R: Read>(input: &mut HXAReader<R>) -> Result<HXAFile, HXAError> {
+        let new_hxa_file = HXAFile::from_hxa_reader(input)?;
+        reject_skipped_nodes(input.warnings())?;
+
+        Ok(new_hxa_file)
+    }
+
+    /// Parses a HxA file that is already in memory
+    /// # Example
+    /// ```rust
+    /// // "HxA\0" magic, version 3 and no nodes
+    /// let bytes = [0x48, 0x78, 0x41, 0x00, 3, 0, 0, 0, 0];
+    /// let my_hxa = hxa::HXAFile::from_bytes(&bytes).expect("Expected a valid file");
+    /// assert_eq!(my_hxa.node_count, 0);
+    /// ```
+    pub fn from_bytes(bytes: &[u8]) -> Result<HXAFile, HXAError> {
+        HXAFile::from_bytes_with(bytes, LoadOptions::default())
+    }
+
+    /// Parses a HxA file that is already in memory under the given limits
+    pub fn from_bytes_with(bytes: &[u8], options: LoadOptions) -> Result<HXAFile, HXAError> {
+        let mut input = HXAReader::with_options(bytes, options, Some(bytes.len() as u64));
+        HXAFile::from_hxa_reader_strict(&mut input)
+    }
+
+    pub fn read_header<R: Read>(self: &mut HXAFile, input: &mut HXAReader<R>) -> Result<(), HXAError>{
+        (self.version, self.node_count) = read_preamble(input)?;
+        self.magic_number = HXA_MAGIC_NUMBER;
+
+        self.read_nodes(input)
+    }
 
-        //Read node count
-        self.node_count = read_bytes!{input u32};
+    /// Reads `node_count` nodes, only nodes that were read completely are added to `node_array`
+    fn read_nodes<R: Read>(self: &mut HXAFile, input: &mut HXAReader<R>) -> Result<(), HXAError>{
+        let node_count = self.node_count;
+        input.report_nodes(0, node_count)?;
 
-        for _ in 0..self.node_count{
+        for node_index in 0..self.node_count{
+            let node_offset = whereami!(input);
+            input.skip_node = !input.options().filter.wants_node_index(node_index);
             let mut new_node = HXANode::new();
-            new_node.parse(input);
-            self.node_array.push(new_node);
+            new_node.parse(input).map_err(|err| err.within(format!("node[{}]", node_index)))?;
+
+            if !input.finish_unknown_node(&mut new_node, node_index, self.node_count, node_offset)? {
+                break;
+            }
+
+            if !input.skip_node {
+                self.node_array.push(new_node);
+            }
+            input.report_nodes(node_index + 1, node_count)?;
         }
+        // Nodes that were skipped or filtered out are not counted
+        self.node_count = self.node_array.len() as u32;
+
+        Ok(())
+    }
+
+    /// Opens the HxA file at `path` and salvages every node that can be read completely
+    /// 
+    /// Meant for files that were cut short or damaged, for example by an exporter that crashed while writing.
+    /// Fails only if the header itself cannot be read
+    pub fn recover<P: AsRef<Path>>(path: P) -> Result<HXARecovery, HXAError> {
+        let file = File::open(path)?;
+        let length = file.metadata()?.len();
+
+        let mut input = HXAReader::with_options(BufReader::new(file), LoadOptions::default(), Some(length));
+        HXAFile::recover_from_hxa_reader(&mut input)
+    }
+
+    /// Salvages every node that can be read completely from a HxA file that is already in memory
+    pub fn recover_bytes(bytes: &[u8]) -> Result<HXARecovery, HXAError> {
+        let mut input = HXAReader::with_options(bytes, LoadOptions::default(), Some(bytes.len() as u64));
+        HXAFile::recover_from_hxa_reader(&mut input)
+    }
+
+    /// Salvages every node that can be read completely from a reader that already carries its `LoadOptions`
+    pub fn recover_from_hxa_reader<R: Read>(input: &mut HXAReader<R>) -> Result<HXARecovery, HXAError> {
+        let mut new_hxa_file = HXAFile::new();
+        (new_hxa_file.version, new_hxa_file.node_count) = read_preamble(input)?;
+
+        let expected_nodes = new_hxa_file.node_count;
+        let stopped = new_hxa_file.read_nodes(input).err();
+        new_hxa_file.node_count = new_hxa_file.node_array.len() as u32;
+
+        Ok(HXARecovery {
+            file: new_hxa_file,
+            expected_nodes,
+            stopped,
+        })
+    }
+
+    /// Lists the nodes, layers and sizes in the HxA file at `path` without loading any layer data
+    ///
+    /// Layer payloads are seeked over, so this stays fast for large files
+    pub fn peek<P: AsRef<Path>>(path: P) -> Result<HXAFileSummary, HXAError> {
+        let mut input = HXAReader::seekable(BufReader::new(File::open(path)?), LoadOptions::default())?;
+        HXAFileSummary::read(&mut input)
+    }
+
+    /// Lists the nodes, layers and sizes in a HxA file that is already in memory
+    pub fn peek_bytes(bytes: &[u8]) -> Result<HXAFileSummary, HXAError> {
+        let mut input = HXAReader::seekable(Cursor::new(bytes), LoadOptions::default())?;
+        HXAFileSummary::read(&mut input)
+    }
+
+    /// Writes the file in the binary HxA format
+    /// 
+    /// `node_count`, `metadata_count`, `layer_count` and `data_length` are recomputed from the vectors they describe,
+    /// so they don't have to be kept up to date when editing a file
+    pub fn write_to<W: Write>(&self, output: W) -> Result<(), HXAError> {
+        self.write_to_observed(output, |_: &HXAProgress| true)
+    }
+
+    /// Writes the file in the binary HxA format, reporting progress to `observer` after each node
+    ///
+    /// If the observer cancels, the output is left with only part of the file
+    pub fn write_to_observed<W: Write, O: HXAObserver>(&self, output: W, mut observer: O) -> Result<(), HXAError> {
+        let mut output = CountingWriter{ inner: output, written: 0 };
+        write_bytes!(output HXA_MAGIC_NUMBER);
+        write_bytes!(output self.version);
+        write_len!(output self.node_array.len());
+
+        let node_count = self.node_array.len() as u32;
+        for (node_index, node) in self.node_array.iter().enumerate() {
+            node.write(&mut output).map_err(|err| err.within(format!("node[{}]", node_index)))?;
+
+            let progress = HXAProgress {
+                bytes: output.written,
+                total_bytes: None,
+                nodes_done: node_index as u32 + 1,
+                node_count,
+            };
+            if !observer.progress(&progress) {
+                return Err(HXAError::Cancelled.within(format!("node[{}]", node_index)));
+            }
+        }
+
+        output.flush()?;
+        Ok(())
+    }
+
+    /// Writes the file in the binary HxA format to `path`, replacing any existing file
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), HXAError> {
+        self.write_to(BufWriter::new(File::create(path)?))
+    }
+
+    /// Writes the file to `path` like `save`, reporting progress to `observer` which can cancel the save
+    pub fn save_observed<P: AsRef<Path>, O: HXAObserver>(&self, path: P, observer: O) -> Result<(), HXAError> {
+        self.write_to_observed(BufWriter::new(File::create(path)?), observer)
     }
 
     /// Returns the first geometry node found
@@ -57,27 +287,68 @@ impl HXAFile {
     /// 
     pub fn get_first_geometry(&self) -> Option<(&HXAGeometryNode, &Vec<HXAMeta>)> {
         for node in &self.node_array{
-            match &node.node_type {
-                crate::enums::HXANodeType::Geometry(gnode) => {
-                    return Some((gnode,&node.meta_data));
-                },
-                _ => {},
+            if let HXANodeType::Geometry(gnode) = &node.node_type {
+                return Some((gnode,&node.meta_data));
             }
         }
-        return None;
+        None
     }
 }
 
-impl From<&str> for HXAFile {
-    fn from(value: &str) -> Self {
-        let mut f = BufReader::new(
-            File::open(value)
-            .expect("Failed to open file")
-        );
+/// The result of loading a damaged file with `HXAFile::recover`
+#[derive(Debug)]
+pub struct HXARecovery {
+    /// Every node that was read completely, in file order
+    pub file: HXAFile,
 
-        let mut new_hxa_file = HXAFile::new();
-        new_hxa_file.read_header(&mut f);
+    /// Number of nodes the header says the file holds
+    pub expected_nodes: u32,
 
-        new_hxa_file
+    /// Why reading stopped early, with the byte offset and path of the failure. `None` if every node was read
+    pub stopped: Option<HXAError>,
+}
+
+impl HXARecovery {
+    /// True if every node in the file was read
+    pub fn is_complete(&self) -> bool {
+        self.stopped.is_none()
+    }
+}
+
+/// Reads and checks the magic number and version, returning the version and the node count
+pub(crate) fn read_preamble<R: Read>(input: &mut HXAReader<R>) -> Result<(u8, u32), HXAError> {
+    //Read magic number
+    let magic_number = read_bytes!{input u32};
+    if magic_number != HXA_MAGIC_NUMBER {
+        return Err(HXAError::BadMagic(magic_number).at(whereami!(input) - 4));
+    }
+
+    //Read version number
+    let version = read_bytes!{input u8};
+    if !HXA_SUPPORTED_VERSIONS.contains(&version) {
+        return Err(HXAError::UnsupportedVersion(version).at(whereami!(input) - 1));
+    }
+
+    //Read node count
+    let node_count = read_bytes!{input u32};
+    input.check_count("max_nodes", node_count, input.options().max_nodes)?;
+
+    Ok((version, node_count))
+}
+
+impl Default for HXAFile {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Opens and parses the HxA file at the given path
+/// # Panics
+/// Panics if the file cannot be opened or is not a valid HxA file
+/// ## Recommendation
+/// Use `HXAFile::open` to handle load failures without panicking
+impl From<&str> for HXAFile {
+    fn from(value: &str) -> Self {
+        HXAFile::open(value).expect("Failed to load HxA file")
     }
 }
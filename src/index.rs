@@ -0,0 +1,100 @@
+use crate::error::{HXAError, reject_skipped_nodes};
+use crate::node::HXANode;
+use crate::options::LoadOptions;
+use crate::reader::HXAReader;
+use crate::summary::HXAFileSummary;
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// A HxA file with the byte offset of every node, so single nodes can be loaded without reading the others
+///
+/// Opening it makes one pass over the file that skips all layer data, see `HXAFile::peek`.
+/// Loading a node then seeks straight to it.
+/// # Example
+/// ```rust,no_run
+/// let mut lods = hxa::HXAIndexedFile::open("Rock_LODs.hxa").expect("Expected a valid file");
+/// let lowest = lods.node_count() - 1;
+/// let node = lods.load_node(lowest).expect("Expected the node to load");
+/// ```
+#[derive(Debug)]
+pub struct HXAIndexedFile<R: Read + Seek> {
+    inner: R,
+
+    /// Position of the file in `inner`, offsets are relative to it
+    start: u64,
+    options: LoadOptions,
+    summary: HXAFileSummary,
+}
+
+impl HXAIndexedFile<BufReader<File>> {
+    /// Opens and indexes the HxA file at `path`
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, HXAError> {
+        HXAIndexedFile::new(BufReader::new(File::open(path)?), LoadOptions::default())
+    }
+}
+
+impl<R: Read + Seek> HXAIndexedFile<R> {
+    /// Indexes the HxA file that starts at the current position of `inner`
+    ///
+    /// The limits and layer filters of `options` apply to every node loaded, node filters are not used
+    pub fn new(mut inner: R, options: LoadOptions) -> Result<Self, HXAError> {
+        let mut options = options;
+        options.filter.node_indices = None;
+        options.filter.node_types = None;
+
+        let start = inner.stream_position()?;
+        let mut input = HXAReader::seekable(inner, options.clone())?;
+        let summary = HXAFileSummary::read(&mut input)?;
+        reject_skipped_nodes(input.warnings())?;
+
+        Ok(HXAIndexedFile{
+            inner: input.into_inner(),
+            start,
+            options,
+            summary,
+        })
+    }
+
+    /// Number of nodes that can be loaded
+    pub fn node_count(&self) -> usize {
+        self.summary.nodes.len()
+    }
+
+    /// Byte offset of every node from the start of the file, in `node_array` order
+    pub fn node_offsets(&self) -> Vec<u64> {
+        self.summary.nodes.iter().map(|node| node.offset).collect()
+    }
+
+    /// The nodes, layers and meta data found while indexing, useful to pick which node to load
+    pub fn summary(&self) -> &HXAFileSummary {
+        &self.summary
+    }
+
+    /// Seeks to the node at `node_index` and loads only that node
+    ///
+    /// Fails with `HXAError::NoSuchNode` if `node_index` is not below `node_count`
+    pub fn load_node(&mut self, node_index: usize) -> Result<HXANode, HXAError> {
+        let offset = match self.summary.nodes.get(node_index) {
+            Some(node) => node.offset,
+            None => return Err(HXAError::NoSuchNode { node_index, node_count: self.node_count() }),
+        };
+        self.inner.seek(SeekFrom::Start(self.start + offset))?;
+
+        let mut input = HXAReader::seekable(&mut self.inner, self.options.clone())?;
+        input.starting_at(offset);
+
+        let mut node = HXANode::new();
+        node.parse(&mut input).map_err(|err| err.within(format!("node[{}]", node_index)))?;
+
+        // Only a final unknown node is indexed, its payload is the rest of the file
+        input.finish_unknown_node(&mut node, node_index as u32, self.summary.node_count, offset)?;
+
+        Ok(node)
+    }
+
+    /// Returns the underlying reader
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
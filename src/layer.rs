@@ -1,7 +1,8 @@
 use crate::enums::HXALayerDataType;
-use crate::macros::{buffer,read_bytes,whereami,read_str};
-use std::fs::File;
-use std::io::{BufReader, Read, Seek};
+use crate::error::HXAError;
+use crate::macros::{buffer,read_bytes,read_str,read_vec,whereami,write_bytes,write_len,write_name};
+use crate::reader::HXAReader;
+use std::io::{Read, Write};
 use std::{str};
 
 
@@ -19,13 +20,14 @@ pub struct  HXALayer {
 impl HXALayer {
     fn new() -> Self {
         HXALayer {
-            name: String::from("NO NAME"),
+            name: String::new(),
             components: 0,
             layer_type: HXALayerDataType::Unknown,
         }
     }
 
-    fn parse(self: &mut HXALayer, input: &mut BufReader<File>, num_items: &u32) {
+    /// Parses a layer, returns `false` if the `LoadFilter` left it out, in which case its data was skipped
+    fn parse<R: Read>(self: &mut HXALayer, input: &mut HXAReader<R>, num_items: &u32) -> Result<bool, HXAError> {
         // Get the name of the layer
         //whereami!(input);
         let name_length:u8 = read_bytes!(input u8);
@@ -40,35 +42,89 @@ impl HXALayer {
         let u8_layer_type:u8 = read_bytes!(input u8);
         self.layer_type = HXALayerDataType::from(u8_layer_type);
 
+        // Number of values in the layer, the file has no length of its own for it
+        let value_count = match (*num_items as usize).checked_mul(self.components as usize) {
+            Some(count) => count,
+            // Only reachable where usize is 32 bits, such a layer could not fit in memory anyway
+            None => return Err(HXAError::Truncated.at(whereami!(input))),
+        };
+
+        // Summaries keep every layer without its data, filtered layers are dropped
+        let keep = input.skip_layer_data || (!input.skip_node && input.options().filter.wants_layer(self));
+        if input.skip_layer_data || !keep {
+            let type_size = match self.layer_type.value_size() {
+                Some(type_size) => type_size,
+                None => return Err(HXAError::UnknownLayerType(u8_layer_type).at(whereami!(input) - 1)),
+            };
+            input.skip((value_count as u64).saturating_mul(type_size as u64))?;
+            return Ok(keep);
+        }
+
         match &mut self.layer_type {
             HXALayerDataType::UINT8(uint_array) => {
-                for _ in 0..((*num_items) * (self.components as u32)){
-                    uint_array.push(read_bytes!(input u8));
-                }
+                *uint_array = read_vec!(input u8 value_count);
             },
             HXALayerDataType::INT32(int_array) => {
-                for _ in 0..((*num_items) * (self.components as u32)){
-                    int_array.push(read_bytes!(input i32));
+                *int_array = read_vec!(input i32 value_count);
+            },
+            HXALayerDataType::FLOAT(float_array) => {
+                *float_array = read_vec!(input f32 value_count);
+            },
+            HXALayerDataType::DOUBLE(double_array) => {
+                *double_array = read_vec!(input f64 value_count);
+            },
+            HXALayerDataType::Unknown => return Err(HXAError::UnknownLayerType(u8_layer_type).at(whereami!(input) - 1)),
+        }
+
+        Ok(true)
+    }
+
+    fn write<W: Write>(self: &HXALayer, output: &mut W, num_items: &u32) -> Result<(), HXAError> {
+        write_name!(output &self.name);
+        write_bytes!(output self.components);
+
+        let layer_type = match self.layer_type.type_code() {
+            Some(code) => code,
+            None => return Err(HXAError::Unwritable(String::from("unknown layer type"))),
+        };
+        write_bytes!(output layer_type);
+
+        // The file has no length for layers, the reader relies on the count of the stack
+        let expected = (*num_items as usize) * (self.components as usize);
+        if self.layer_type.len() != expected {
+            return Err(HXAError::Unwritable(format!(
+                "expected {} values ({} items with {} components) but the layer holds {}",
+                expected, num_items, self.components, self.layer_type.len()
+            )));
+        }
+
+        match &self.layer_type {
+            HXALayerDataType::UINT8(uint_array) => output.write_all(uint_array)?,
+            HXALayerDataType::INT32(int_array) => {
+                for value in int_array {
+                    write_bytes!(output value);
                 }
             },
             HXALayerDataType::FLOAT(float_array) => {
-                for _ in 0..((*num_items) * (self.components as u32)){
-                    float_array.push(read_bytes!(input f32));
+                for value in float_array {
+                    write_bytes!(output value);
                 }
             },
             HXALayerDataType::DOUBLE(double_array) => {
-                for _ in 0..((*num_items) * (self.components as u32)){
-                    double_array.push(read_bytes!(input f64));
+                for value in double_array {
+                    write_bytes!(output value);
                 }
             },
-            HXALayerDataType::Unknown => {},
+            HXALayerDataType::Unknown => (),
         }
+
+        Ok(())
     }
 
     pub fn try_as_vec_i32(&self) -> Option<&Vec<i32>>{
         match &self.layer_type{
-            HXALayerDataType::INT32(int_array) => return Some(int_array),
-            _ => return None,
+            HXALayerDataType::INT32(int_array) => Some(int_array),
+            _ => None,
         }
     }
 
@@ -83,8 +139,8 @@ impl HXALayer {
 
     pub fn try_as_vec_f32(&self) -> Option<&Vec<f32>>{
         match &self.layer_type{
-            HXALayerDataType::FLOAT(float_array) => return Some(float_array),
-            _ => return None,
+            HXALayerDataType::FLOAT(float_array) => Some(float_array),
+            _ => None,
         }
     }
 
@@ -105,6 +161,7 @@ impl HXALayer {
     /// Use this function if following a `HxA` standard where  a `HXALayer` with a specific name always has a specific type
     /// 
     /// This was designed to make it easier to move data into `Vertex` objects from libraries such as `Vulkano` and `glium`
+    #[deprecated(note = "use `HXAVertexBufferBuilder`, which also handles corner layers and `DOUBLE` values")]
     pub fn as_tri_tup_vec_f32(&self) -> Vec<(f32,f32,f32)>{
         
         let vec_ref = self.as_vec_f32();
@@ -136,6 +193,7 @@ impl HXALayer {
     /// Use this function if following a `HxA` standard where  a `HXALayer` with a specific name always has a specific type
     /// 
     /// This was designed to make it easier to move data into `Vertex` objects from libraries such as `Vulkano` and `glium`
+    #[deprecated(note = "use `HXAVertexBufferBuilder`, which also handles corner layers and `DOUBLE` values")]
     pub fn as_di_tup_vec_f32(&self) -> Vec<(f32,f32)>{
         
         let vec_ref = self.as_vec_f32();
@@ -160,8 +218,8 @@ impl HXALayer {
 
     pub fn try_as_vec_f64(&self) -> Option<&Vec<f64>>{
         match &self.layer_type{
-            HXALayerDataType::DOUBLE(double_array) => return Some(double_array),
-            _ => return None,
+            HXALayerDataType::DOUBLE(double_array) => Some(double_array),
+            _ => None,
         }
     }
 
@@ -176,8 +234,8 @@ impl HXALayer {
 
     pub fn try_as_vec_u8(&self) -> Option<&Vec<u8>>{
         match &self.layer_type{
-            HXALayerDataType::UINT8(uint_array) => return Some(uint_array),
-            _ => return None,
+            HXALayerDataType::UINT8(uint_array) => Some(uint_array),
+            _ => None,
         }
     }
 
@@ -205,28 +263,73 @@ impl HXALayerStack {
         }
     }
 
-    pub fn parse(self: &mut HXALayerStack, input: &mut BufReader<File>, num_items: &u32){
+    pub fn parse<R: Read>(self: &mut HXALayerStack, input: &mut HXAReader<R>, num_items: &u32) -> Result<(), HXAError>{
 
         self.layer_count = read_bytes!(input u32);
+        input.check_count("max_layers", self.layer_count, input.options().max_layers)?;
 
-        for _ in 0..self.layer_count{
+        for layer_index in 0..self.layer_count{
             let mut new_layer = HXALayer::new();
-            new_layer.parse(input, num_items);
-            self.layers.push(new_layer)
+            let keep = new_layer.parse(input, num_items).map_err(|err| {
+                // Name the layer if we got far enough to read its name
+                if new_layer.name.is_empty() {
+                    err.within(format!("layer[{}]", layer_index))
+                } else {
+                    err.within(format!("layer[{:?}]", new_layer.name))
+                }
+            })?;
+            if keep {
+                self.layers.push(new_layer)
+            }
+            input.report()?;
+        }
+        self.layer_count = self.layers.len() as u32;
+
+        Ok(())
+    }
+
+    /// Writes the stack, `layer_count` is taken from the number of layers rather than the stored field
+    /// 
+    /// Every layer must hold exactly `num_items` times its components values
+    pub fn write<W: Write>(self: &HXALayerStack, output: &mut W, num_items: &u32) -> Result<(), HXAError>{
+        write_len!(output self.layers.len());
+
+        for (layer_index, layer) in self.layers.iter().enumerate() {
+            layer.write(output, num_items).map_err(|err| {
+                if layer.name.is_empty() {
+                    err.within(format!("layer[{}]", layer_index))
+                } else {
+                    err.within(format!("layer[{:?}]", layer.name))
+                }
+            })?;
         }
+
+        Ok(())
     }
 
     /// Finds the first layer with the specified name
     pub fn find(&self, layer_name:&str) -> Option<&HXALayer>{
-        // In the future I might change layer names to just be string slices too
-        let search_string = String::from(layer_name);
+        self.layers.iter().find(|layer| layer.name == layer_name)
+    }
 
-        for layer in &self.layers{
-            if layer.name == search_string{
-                return Some(layer);
-            }
-        }
-        None
+    /// Adds a layer after the existing ones and updates `layer_count`
+    pub fn push(&mut self, layer: HXALayer) {
+        self.layers.push(layer);
+        self.layer_count = self.layers.len() as u32;
+    }
+
+    /// Removes every layer with the specified name and updates `layer_count`, returns whether any was removed
+    pub fn remove(&mut self, layer_name: &str) -> bool {
+        let before = self.layers.len();
+        self.layers.retain(|layer| layer.name != layer_name);
+        self.layer_count = self.layers.len() as u32;
+        self.layers.len() != before
+    }
+}
+
+impl Default for HXALayerStack {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -1,13 +1,45 @@
 pub mod enums;
+pub mod error;
 pub mod meta;
 pub mod node;
 pub mod layer;
 pub mod hxfile;
 pub mod conventions;
+pub mod options;
+pub mod reader;
+pub mod view;
+pub mod summary;
+pub mod index;
+pub mod parser;
+pub mod progress;
+pub mod validation;
+pub mod schema;
+pub mod polygon;
+pub mod triangulate;
+pub mod buffer;
+pub mod neighbour;
+pub mod normals;
 
 mod macros;
+#[cfg(feature = "tokio")]
+mod async_io;
 
-pub use hxfile::HXAFile;
+pub use error::{HXAError, HXAWarning};
+pub use hxfile::{HXAFile, HXARecovery, HXA_MAGIC_NUMBER, HXA_VERSION_FORMAT, HXA_SUPPORTED_VERSIONS};
+pub use options::{LoadOptions, LoadFilter, LayerPredicate};
+pub use reader::HXAReader;
 pub use layer::{HXALayer,HXALayerStack};
 pub use meta::HXAMeta;
-pub use node::{HXANode,HXAGeometryNode};
\ No newline at end of file
+pub use node::{HXANode,HXAGeometryNode,HXAImageNode};
+pub use index::HXAIndexedFile;
+pub use parser::HXAParser;
+pub use progress::{HXAObserver, HXAProgress};
+pub use validation::{HXASeverity, HXAGeometryIssue};
+pub use schema::{HXASchema, HXASchemaIssue};
+pub use polygon::{HXAPolygon, HXAPolygons};
+pub use triangulate::{HXATriangulation, HXATriangles};
+pub use buffer::{HXAVertexBufferBuilder, HXAVertexBuffer, HXAVertexAttribute};
+pub use view::HXAFileView;
+pub use summary::{HXAFileSummary, HXANodeSummary, HXANodeSummaryType, HXAGeometrySummary, HXAImageSummary, HXALayerSummary};
+#[cfg(feature = "mmap")]
+pub use view::HXAMappedFile;
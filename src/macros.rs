@@ -1,9 +1,7 @@
+/// Gets the current byte offset of the `HXAReader`
 macro_rules! whereami {
     ($reader:tt) => {
-        {
-            let file_location = $reader.seek(std::io::SeekFrom::Current(0)).expect("Could not get current position!");
-            println!("I am here:  {:X?}  !",file_location)
-        }
+        $reader.position()
     };
 }
 
@@ -19,49 +17,135 @@ macro_rules!  buffer{
 }
 
 /// Reads enough bytes to produce the desired type
+///
+/// Returns early with a `HXAError` located where the reader ran out of data
 /// # Example
-/// ```rust
+/// ```rust,ignore
 /// let foo:u32 = read_bytes!(input u32);
 /// ```
 macro_rules!  read_bytes{
     ($r:tt $typ:tt) => {
         {
             let mut buffer = buffer!($typ);
-            let result = $r.read_exact(&mut buffer);
-            match result {
-                Ok(_) => {
-                    // whereami!($r);
-                    // println!("^^ Location, OK read type:{}",stringify!($typ))
-                },
-                Err(_) => {
-                    println!("read_type: {}",stringify!($typ));
-                    panic!("Failed read_bytes")
-                },
+            if let Err(err) = $r.read_exact(&mut buffer) {
+                return Err(HXAError::from(err).at(whereami!($r)));
             }
             $typ::from_le_bytes(buffer)
         }
     }
 }
 
+/// Reads `count` little endian values of the desired type into a vector
+///
+/// When the length of the input is known the vector is sized up front and filled with a single read,
+/// otherwise it is decoded from `HXAReader::read_payload` so a bogus count cannot allocate more than the input holds.
+/// Returns early with a `HXAError` if the values cannot fit in the rest of the input or the `LoadOptions` limits
+/// # Example
+/// ```rust,ignore
+/// let foo:Vec<f32> = read_vec!(input f32 vertex_count * 3);
+/// ```
+macro_rules!  read_vec{
+    ($r:tt $typ:tt $count:expr) => {
+        {
+            let count:usize = $count;
+            let size = (count as u64).saturating_mul(std::mem::size_of::<$typ>() as u64);
+
+            if $r.remaining().is_some() {
+                $r.reserve(size)?;
+                let mut values:Vec<$typ> = vec![<$typ>::default(); count];
+
+                // Safety: the numeric types stored in HxA have no padding and every bit pattern is valid
+                let bytes = unsafe {
+                    std::slice::from_raw_parts_mut(values.as_mut_ptr() as *mut u8, count * std::mem::size_of::<$typ>())
+                };
+                if let Err(err) = $r.read_exact(bytes) {
+                    return Err(HXAError::from(err).at(whereami!($r)));
+                }
+
+                // The bytes were copied as they are in the file, swap them on hosts that are not little endian
+                if cfg!(target_endian = "big") {
+                    for value in values.iter_mut() {
+                        *value = <$typ>::from_le_bytes(value.to_ne_bytes());
+                    }
+                }
+                values
+            } else {
+                $r.read_payload(size)?
+                    .chunks_exact(std::mem::size_of::<$typ>())
+                    .map(|value| <$typ>::from_le_bytes(value.try_into().expect("Expected chunks of the type size")))
+                    .collect::<Vec<$typ>>()
+            }
+        }
+    }
+}
+
 /// Reads enough bytes to produce an str
+///
+/// Returns early with a `HXAError` located where the reader ran out of data or where the invalid utf8 begins
 macro_rules!  read_str{
     ($reader:ident $buffer:tt) => {
         {
-            let result = $reader.read_exact(&mut $buffer);
-            match result {
-                Ok(_) => (),
-                Err(_) => {
-                    whereami!($reader);
-                    println!("read_type: {}",stringify!($buffer));
-                    panic!("Expected to read more bytes")
+            if let Err(err) = $reader.read_exact(&mut $buffer) {
+                return Err(HXAError::from(err).at(whereami!($reader)));
+            }
+            match str::from_utf8(&$buffer) {
+                Ok(slice) => slice,
+                Err(err) => {
+                    let start = whereami!($reader) - $buffer.len() as u64;
+                    return Err(HXAError::from(err).at(start + err.valid_up_to() as u64));
                 },
             }
-            str::from_utf8(&$buffer).expect("Expected a valid utf8 format")
+        }
+    }
+}
+
+/// Writes the little endian bytes of a value
+/// # Example
+/// ```rust,ignore
+/// write_bytes!(output 3u8);
+/// ```
+macro_rules!  write_bytes{
+    ($w:tt $value:expr) => {
+        $w.write_all(&$value.to_le_bytes())?
+    }
+}
+
+/// Writes the length of a vector or string as the `u32` count that precedes it in the file
+///
+/// Returns early with `HXAError::Unwritable` if the length does not fit
+macro_rules!  write_len{
+    ($w:tt $len:expr) => {
+        {
+            let length = $len;
+            match u32::try_from(length) {
+                Ok(count) => write_bytes!($w count),
+                Err(_) => return Err(HXAError::Unwritable(format!("{} items do not fit in a u32 count", length))),
+            }
+        }
+    }
+}
+
+/// Writes a name as a `u8` length followed by its bytes
+///
+/// Returns early with `HXAError::Unwritable` if the name is longer than 255 bytes
+macro_rules!  write_name{
+    ($w:tt $name:expr) => {
+        {
+            let name: &str = $name;
+            match u8::try_from(name.len()) {
+                Ok(length) => write_bytes!($w length),
+                Err(_) => return Err(HXAError::Unwritable(format!("the name {:?} is longer than 255 bytes", name))),
+            }
+            $w.write_all(name.as_bytes())?;
         }
     }
 }
 
 pub(crate) use read_str;
 pub(crate) use read_bytes;
+pub(crate) use read_vec;
 pub(crate) use buffer;
-pub(crate) use whereami;
\ No newline at end of file
+pub(crate) use whereami;
+pub(crate) use write_bytes;
+pub(crate) use write_len;
+pub(crate) use write_name;
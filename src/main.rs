@@ -1,5 +1,3 @@
-use hxa;
-
 fn main() {
     println!("Opening file...");
 
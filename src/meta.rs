@@ -1,7 +1,8 @@
 use crate::enums::HXAMetaDataType;
-use crate::macros::{buffer,read_bytes,whereami,read_str};
-use std::fs::File;
-use std::io::{BufReader, Read, Seek};
+use crate::error::HXAError;
+use crate::macros::{buffer,read_bytes,read_str,read_vec,whereami,write_bytes,write_len,write_name};
+use crate::reader::HXAReader;
+use std::io::{Read, Write};
 use std::str;
 
 #[derive(Debug)]
@@ -21,7 +22,7 @@ impl HXAMeta {
         }
     }
 
-    pub fn parse(self: &mut HXAMeta, input: &mut BufReader<File>){
+    pub fn parse<R: Read>(self: &mut HXAMeta, input: &mut HXAReader<R>) -> Result<(), HXAError>{
 
         // Length of the name in bytes
         let name_length:u8 = read_bytes!(input u8);
@@ -31,39 +32,111 @@ impl HXAMeta {
 
         self.name = String::from(data_name);
 
-        self.meta_type = HXAMetaDataType::from(read_bytes!(input u8));
+        let u8_meta_type:u8 = read_bytes!(input u8);
+        self.meta_type = HXAMetaDataType::from(u8_meta_type);
+        if let HXAMetaDataType::COUNT | HXAMetaDataType::Unknown = self.meta_type {
+            // Without a known type there is no way to tell how many bytes the payload takes
+            return Err(HXAError::UnknownMetaType(u8_meta_type).at(whereami!(input) - 1));
+        }
+
         self.data_length = read_bytes!(input u32);
 
         match &mut self.meta_type {
             HXAMetaDataType::INT64 { int_array } => {
-                for _ in 0..self.data_length{
-                    let int64_read = read_bytes!(input i64);
-                    int_array.push(int64_read);
-                }
+                *int_array = read_vec!(input i64 self.data_length as usize);
             }
             HXAMetaDataType::DOUBLE { double_array } => {
-                for _ in 0..self.data_length{
-                    let double_read = read_bytes!(input f64);
-                    double_array.push(double_read);
-                }
+                *double_array = read_vec!(input f64 self.data_length as usize);
+            },
+
+            HXAMetaDataType::NODE { node_array } => {
+                *node_array = read_vec!(input u32 self.data_length as usize);
             },
 
             HXAMetaDataType::TEXT { text } => {
-                let mut meta_text_buffer = buffer!(exactly self.data_length);
-                let slice = read_str!(input meta_text_buffer);
-                *text = String::from(slice);
+                let meta_text_buffer = input.read_payload(self.data_length as u64)?;
+                *text = match String::from_utf8(meta_text_buffer) {
+                    Ok(meta_text) => meta_text,
+                    Err(err) => {
+                        let start = whereami!(input) - self.data_length as u64;
+                        return Err(HXAError::from(err.utf8_error()).at(start + err.utf8_error().valid_up_to() as u64));
+                    },
+                };
+            },
+
+            HXAMetaDataType::BINARY { binary_array } => {
+                *binary_array = read_vec!(input u8 self.data_length as usize);
             },
 
             HXAMetaDataType::META { meta_array } => {
                 //We expect self.data_length more meta datas
 
-                for _ in 0..self.data_length{
+                input.enter()?;
+                for meta_index in 0..self.data_length{
                     let mut new_metadata = HXAMeta::new();
-                    new_metadata.parse(input);
+                    new_metadata.parse(input).map_err(|err| err.within(format!("meta[{}]", meta_index)))?;
                     meta_array.push(new_metadata);
                 }
+                input.leave();
+            },
+            HXAMetaDataType::COUNT | HXAMetaDataType::Unknown => unreachable!("Unknown meta data types are rejected when the type is read"),
+        }
+
+        Ok(())
+    }
+
+    /// Writes the meta data, `data_length` is taken from the payload rather than the stored field
+    pub fn write<W: Write>(self: &HXAMeta, output: &mut W) -> Result<(), HXAError>{
+        write_name!(output &self.name);
+
+        let meta_type = match self.meta_type.type_code() {
+            Some(code) => code,
+            None => return Err(HXAError::Unwritable(format!("meta data {:?} has no valid type", self.name))),
+        };
+        write_bytes!(output meta_type);
+
+        match &self.meta_type {
+            HXAMetaDataType::INT64 { int_array } => {
+                write_len!(output int_array.len());
+                for int64 in int_array {
+                    write_bytes!(output int64);
+                }
+            },
+            HXAMetaDataType::DOUBLE { double_array } => {
+                write_len!(output double_array.len());
+                for double in double_array {
+                    write_bytes!(output double);
+                }
+            },
+            HXAMetaDataType::NODE { node_array } => {
+                write_len!(output node_array.len());
+                for node_index in node_array {
+                    write_bytes!(output node_index);
+                }
+            },
+            HXAMetaDataType::TEXT { text } => {
+                write_len!(output text.len());
+                output.write_all(text.as_bytes())?;
+            },
+            HXAMetaDataType::BINARY { binary_array } => {
+                write_len!(output binary_array.len());
+                output.write_all(binary_array)?;
+            },
+            HXAMetaDataType::META { meta_array } => {
+                write_len!(output meta_array.len());
+                for (meta_index, meta_data) in meta_array.iter().enumerate() {
+                    meta_data.write(output).map_err(|err| err.within(format!("meta[{}]", meta_index)))?;
+                }
             },
-            _ => {}
+            HXAMetaDataType::COUNT | HXAMetaDataType::Unknown => (),
         }
+
+        Ok(())
+    }
+}
+
+impl Default for HXAMeta {
+    fn default() -> Self {
+        Self::new()
     }
 }
\ No newline at end of file
@@ -0,0 +1,96 @@
+use crate::conventions::hard;
+use crate::enums::HXALayerDataType;
+use crate::error::HXAError;
+use crate::layer::HXALayer;
+use crate::node::HXAGeometryNode;
+use crate::polygon::HXAPolygons;
+use std::collections::{HashMap, VecDeque};
+
+/// Value of the `neighbour` layer for an edge that no other polygon shares
+pub const NO_NEIGHBOUR: i32 = -1;
+
+/// Builds the values of a `neighbour` layer from the values of a `reference` layer
+///
+/// The edge of a corner runs from its vertex to the vertex of the next corner in the polygon.
+/// Each edge stores the corner of the other polygon whose edge runs the opposite way between the
+/// same two vertices, or `NO_NEIGHBOUR` when there is none. Where more than two edges meet,
+/// they are paired in the order their corners appear.
+pub fn compute_neighbours(references: &[i32]) -> Vec<i32> {
+    let mut neighbours = vec![NO_NEIGHBOUR; references.len()];
+
+    // Edges that have not found their opposite yet, keyed by the vertices they run from and to
+    let mut open_edges: HashMap<(u32, u32), VecDeque<usize>> = HashMap::new();
+
+    for polygon in HXAPolygons::new(references) {
+        for corner in 0..polygon.len() {
+            let from = polygon.vertex(corner);
+            let to = polygon.vertex((corner + 1) % polygon.len());
+            let corner = polygon.corners.start + corner;
+
+            match open_edges.get_mut(&(to, from)).and_then(|corners| corners.pop_front()) {
+                Some(opposite) => {
+                    neighbours[corner] = opposite as i32;
+                    neighbours[opposite] = corner as i32;
+                },
+                None => open_edges.entry((from, to)).or_default().push_back(corner),
+            }
+        }
+    }
+
+    neighbours
+}
+
+impl HXAGeometryNode {
+    /// Computes the `neighbour` layer from the `reference` layer, `None` if there is no `INT32` `reference` layer
+    ///
+    /// See `compute_neighbours` for how the values are encoded
+    pub fn try_neighbour_layer(&self) -> Option<HXALayer> {
+        let references = self.corner_stack.find(hard::CORNER_LAYER_NAME)?.try_as_vec_i32()?;
+        Some(HXALayer {
+            name: String::from(hard::NEIGHBOUR_LAYER_NAME),
+            components: 1,
+            layer_type: HXALayerDataType::INT32(compute_neighbours(references)),
+        })
+    }
+
+    /// Computes the `neighbour` layer from the `reference` layer
+    /// # Panics
+    /// Panics if there is no `reference` layer or it is not `INT32`
+    pub fn neighbour_layer(&self) -> HXALayer {
+        self.try_neighbour_layer().expect("Expected an INT32 reference layer")
+    }
+
+    /// Adds the `neighbour` layer to the `edge_stack` if it is missing
+    ///
+    /// Returns `false` if the `edge_stack` already had a `neighbour` layer, which is left untouched
+    /// # Example
+    /// ```rust,no_run
+    /// use hxa::enums::HXANodeType;
+    ///
+    /// let mut my_hxa = hxa::HXAFile::from("Cube.hxa");
+    /// for node in &mut my_hxa.node_array {
+    ///     if let HXANodeType::Geometry(geometry) = &mut node.node_type {
+    ///         geometry.add_neighbour_layer().expect("Expected a reference layer");
+    ///
+    ///         let neighbours = geometry.edge_stack.find("neighbour").unwrap().as_vec_i32();
+    ///         let open_edges = neighbours.iter().filter(|neighbour| **neighbour == hxa::neighbour::NO_NEIGHBOUR).count();
+    ///     }
+    /// }
+    /// ```
+    pub fn add_neighbour_layer(&mut self) -> Result<bool, HXAError> {
+        if self.edge_stack.find(hard::NEIGHBOUR_LAYER_NAME).is_some() {
+            return Ok(false);
+        }
+
+        let layer = self.try_neighbour_layer().ok_or_else(|| HXAError::MissingLayer(String::from(hard::CORNER_LAYER_NAME)))?;
+        if layer.layer_type.len() != self.edge_corner_count as usize {
+            return Err(HXAError::InvalidLayer {
+                layer: String::from(hard::CORNER_LAYER_NAME),
+                reason: format!("expected {} values but it holds {}", self.edge_corner_count, layer.layer_type.len()),
+            });
+        }
+
+        self.edge_stack.push(layer);
+        Ok(true)
+    }
+}
@@ -1,10 +1,11 @@
 
+use crate::error::HXAError;
 use crate::layer::HXALayerStack;
 use crate::meta::HXAMeta;
-use crate::enums::HXANodeType;
-use crate::macros::{buffer,read_bytes};
-use std::fs::File;
-use std::io::{BufReader, Read};
+use crate::enums::{HXAImageType, HXANodeType};
+use crate::macros::{buffer,read_bytes,whereami,write_bytes,write_len};
+use crate::reader::HXAReader;
+use std::io::{Read, Write};
 
 #[derive(Debug)]
 pub struct HXANode {
@@ -22,35 +23,106 @@ impl HXANode{
         }
     }
 
-    pub fn parse(self: &mut HXANode, input: &mut BufReader<File>){
+    /// Parses a node, including its meta data and payload
+    /// 
+    /// When the reader allows unknown nodes, a node of unknown type is returned as `HXANodeType::Unsupported`
+    /// with only its meta data read, as the length of its payload is not stored in the file
+    pub fn parse<R: Read>(self: &mut HXANode, input: &mut HXAReader<R>) -> Result<(), HXAError>{
+        self.parse_header(input)?;
+
+        //Get metadata
+        for meta_index in 0 .. self.metadata_count{
+            let meta_data = HXANode::parse_meta(input, meta_index)?;
+            self.meta_data.push(meta_data);
+            // println!("name length:{} {:?} {:?}",name_length, name_buffer, s);
+        }
+
+        self.parse_payload(input)
+    }
+
+    /// Reads the node type and the number of meta data entries
+    pub(crate) fn parse_header<R: Read>(self: &mut HXANode, input: &mut HXAReader<R>) -> Result<(), HXAError>{
 
         //Read node type
-        self.node_type = HXANodeType::from(read_bytes!(input u8)); 
+        let u8_node_type:u8 = read_bytes!(input u8);
+        self.node_type = HXANodeType::from(u8_node_type);
+        if let HXANodeType::Unknown = self.node_type {
+            if !input.options().keep_unknown_nodes {
+                return Err(HXAError::UnknownNodeType(u8_node_type).at(whereami!(input) - 1));
+            }
+            // Every node starts with its meta data, only the payload after it is unknown
+            self.node_type = HXANodeType::Unsupported { type_code: u8_node_type, payload: Vec::new() };
+        }
+        if !input.options().filter.wants_node_type(u8_node_type) {
+            input.skip_node = true;
+        }
 
         
         //Read metadata count
         self.metadata_count = read_bytes!(input u32);
 
-        //Get metadata
-        for _ in 0 .. self.metadata_count{
-            let mut meta_data = HXAMeta::new();
-            meta_data.parse(input);
-            self.meta_data.push(meta_data);
-            // println!("name length:{} {:?} {:?}",name_length, name_buffer, s);
-        }
+        Ok(())
+    }
+
+    /// Reads the meta data entry at `meta_index`
+    pub(crate) fn parse_meta<R: Read>(input: &mut HXAReader<R>, meta_index: u32) -> Result<HXAMeta, HXAError>{
+        let mut meta_data = HXAMeta::new();
+        meta_data.parse(input).map_err(|err| err.within(format!("meta[{}]", meta_index)))?;
+        Ok(meta_data)
+    }
 
+    /// Reads the payload that follows the meta data, which depends on the node type
+    pub(crate) fn parse_payload<R: Read>(self: &mut HXANode, input: &mut HXAReader<R>) -> Result<(), HXAError>{
 
         //Extract data based on node type
         match &mut self.node_type {
             HXANodeType::MetaOnly => (),
             HXANodeType::Geometry(node) => {
-                node.parse(input);
+                node.parse(input).map_err(|err| err.within("geometry"))?;
+            },
+            HXANodeType::Image(node) => {
+                node.parse(input).map_err(|err| err.within("image"))?;
             },
-            HXANodeType::Image => (),
-            HXANodeType::Unknown => panic!("Unknown node type"),
+            // The length is not in the file, the caller decides how much of the input belongs to it
+            HXANodeType::Unsupported { .. } => (),
+            HXANodeType::Unknown => unreachable!("Unknown node types are rejected when the type is read"),
         }
+
+        Ok(())
     }
 
+    pub fn write<W: Write>(self: &HXANode, output: &mut W) -> Result<(), HXAError>{
+        let node_type = match self.node_type.type_code() {
+            Some(code) => code,
+            None => return Err(HXAError::Unwritable(String::from("unknown node type"))),
+        };
+        write_bytes!(output node_type);
+
+        write_len!(output self.meta_data.len());
+        for (meta_index, meta_data) in self.meta_data.iter().enumerate() {
+            meta_data.write(output).map_err(|err| err.within(format!("meta[{}]", meta_index)))?;
+        }
+
+        match &self.node_type {
+            HXANodeType::Geometry(node) => {
+                node.write(output).map_err(|err| err.within("geometry"))?;
+            },
+            HXANodeType::Image(node) => {
+                node.write(output).map_err(|err| err.within("image"))?;
+            },
+            HXANodeType::Unsupported { payload, .. } => output.write_all(payload)?,
+            HXANodeType::MetaOnly | HXANodeType::Unknown => (),
+        }
+
+        Ok(())
+    }
+
+}
+
+impl Default for HXANode {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 #[derive(Debug)]
@@ -90,17 +162,105 @@ impl HXAGeometryNode {
         }
     }
 
-    pub fn parse(self: &mut HXAGeometryNode, input: &mut BufReader<File>){
+    pub fn parse<R: Read>(self: &mut HXAGeometryNode, input: &mut HXAReader<R>) -> Result<(), HXAError>{
         self.vertex_count = read_bytes!(input u32);
-        self.vertex_stack.parse(input, &self.vertex_count);
+        self.vertex_stack.parse(input, &self.vertex_count).map_err(|err| err.within("vertex_stack"))?;
 
 
         self.edge_corner_count = read_bytes!(input u32);
-        self.corner_stack.parse(input, &self.edge_corner_count);
-        self.edge_stack.parse(input, &self.edge_corner_count);
+        self.corner_stack.parse(input, &self.edge_corner_count).map_err(|err| err.within("corner_stack"))?;
+        self.edge_stack.parse(input, &self.edge_corner_count).map_err(|err| err.within("edge_stack"))?;
 
 
         self.face_count = read_bytes!(input u32);
-        self.face_stack.parse(input, &self.face_count);
+        self.face_stack.parse(input, &self.face_count).map_err(|err| err.within("face_stack"))?;
+
+        Ok(())
+    }
+
+    pub fn write<W: Write>(self: &HXAGeometryNode, output: &mut W) -> Result<(), HXAError>{
+        write_bytes!(output self.vertex_count);
+        self.vertex_stack.write(output, &self.vertex_count).map_err(|err| err.within("vertex_stack"))?;
+
+        write_bytes!(output self.edge_corner_count);
+        self.corner_stack.write(output, &self.edge_corner_count).map_err(|err| err.within("corner_stack"))?;
+        self.edge_stack.write(output, &self.edge_corner_count).map_err(|err| err.within("edge_stack"))?;
+
+        write_bytes!(output self.face_count);
+        self.face_stack.write(output, &self.face_count).map_err(|err| err.within("face_stack"))?;
+
+        Ok(())
+    }
+}
+
+impl Default for HXAGeometryNode {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug)]
+pub struct HXAImageNode{
+    /// type of image
+    pub image_type: HXAImageType,
+
+    /// resolution in X, Y and Z dimension, unused dimensions are 1
+    pub resolution: [u32; 3],
+
+    /// the number of values in the stack is equal to the number of pixels depending on resolution
+    pub image_stack: HXALayerStack,
+}
+
+impl HXAImageNode {
+    pub fn new() -> Self{
+        HXAImageNode{
+            image_type: HXAImageType::Unknown,
+            resolution: [1, 1, 1],
+            image_stack: HXALayerStack::new(),
+        }
+    }
+
+    /// Number of pixels in the image, including all 6 sides of a cube image
+    pub fn pixel_count(&self) -> u32 {
+        self.image_type.pixel_count(&self.resolution)
     }
-}
\ No newline at end of file
+
+    pub fn parse<R: Read>(self: &mut HXAImageNode, input: &mut HXAReader<R>) -> Result<(), HXAError>{
+        let u8_image_type:u8 = read_bytes!(input u8);
+        self.image_type = HXAImageType::from(u8_image_type);
+        if let HXAImageType::Unknown = self.image_type {
+            return Err(HXAError::UnknownImageType(u8_image_type).at(whereami!(input) - 1));
+        }
+
+        self.resolution = [1, 1, 1];
+        for dimension in 0..self.image_type.dimensions() {
+            self.resolution[dimension] = read_bytes!(input u32);
+        }
+
+        self.image_stack.parse(input, &self.pixel_count()).map_err(|err| err.within("image_stack"))?;
+
+        Ok(())
+    }
+
+    pub fn write<W: Write>(self: &HXAImageNode, output: &mut W) -> Result<(), HXAError>{
+        let image_type = match self.image_type.type_code() {
+            Some(code) => code,
+            None => return Err(HXAError::Unwritable(String::from("unknown image type"))),
+        };
+        write_bytes!(output image_type);
+
+        for dimension in 0..self.image_type.dimensions() {
+            write_bytes!(output self.resolution[dimension]);
+        }
+
+        self.image_stack.write(output, &self.pixel_count()).map_err(|err| err.within("image_stack"))?;
+
+        Ok(())
+    }
+}
+
+impl Default for HXAImageNode {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,280 @@
+use crate::conventions::{hard, soft};
+use crate::enums::HXALayerDataType;
+use crate::error::HXAError;
+use crate::layer::{HXALayer, HXALayerStack};
+use crate::neighbour::compute_neighbours;
+use crate::node::HXAGeometryNode;
+use crate::polygon::HXAPolygon;
+use crate::triangulate::{corner_positions, newell};
+
+/// How the polygons around a vertex are weighted when their normals are averaged
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HXANormalWeighting {
+    /// Larger polygons count for more
+    Area,
+
+    /// Polygons count for the angle of their corner at the vertex, which does not depend on how they are split up
+    Angle,
+}
+
+/// Which edges `HXAGeometryNode::corner_normals` keeps sharp
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HXAHardEdges {
+    /// Edges with a `creases` value above 0 are hard, ignored when there is no `creases` edge layer
+    pub creases: bool,
+
+    /// Edges where the polygons meet at more than this many degrees are hard
+    pub angle: Option<f64>,
+}
+
+impl HXAGeometryNode {
+    /// The unit normal of each polygon, `None` if the `reference` or `vertex` layer is missing
+    ///
+    /// Polygons that have no area or refer to missing vertices get a zero normal
+    pub fn try_face_normals(&self) -> Option<Vec<[f32; 3]>> {
+        let positions = self.positions()?;
+        Some(self.try_polygons()?.map(|polygon| to_f32(normalize(polygon_normal(&polygon, &positions)))).collect())
+    }
+
+    /// The unit normal of each polygon
+    /// # Panics
+    /// Panics if the `reference` or `vertex` layer is missing
+    pub fn face_normals(&self) -> Vec<[f32; 3]> {
+        self.try_face_normals().expect("Expected a reference and a vertex layer")
+    }
+
+    /// Smooth normals for each vertex, `None` if the `reference` or `vertex` layer is missing
+    ///
+    /// Vertices that no polygon uses get a zero normal
+    pub fn try_vertex_normals(&self, weighting: HXANormalWeighting) -> Option<Vec<[f32; 3]>> {
+        let positions = self.positions()?;
+        let mut sums = vec![[0.0f64; 3]; self.vertex_count as usize];
+
+        for polygon in self.try_polygons()? {
+            let normal = polygon_normal(&polygon, &positions);
+            for (corner, weight) in corner_weights(&polygon, &positions, normal, weighting) {
+                if let Some(sum) = sums.get_mut(polygon.vertex(corner) as usize) {
+                    add(sum, normal, weight);
+                }
+            }
+        }
+
+        Some(sums.into_iter().map(|sum| to_f32(normalize(sum))).collect())
+    }
+
+    /// Smooth normals for each vertex
+    /// # Panics
+    /// Panics if the `reference` or `vertex` layer is missing
+    pub fn vertex_normals(&self, weighting: HXANormalWeighting) -> Vec<[f32; 3]> {
+        self.try_vertex_normals(weighting).expect("Expected a reference and a vertex layer")
+    }
+
+    /// Normals for each corner, smooth across edges except `hard_edges`, `None` if the `reference` or `vertex` layer is missing
+    ///
+    /// Corners share a normal when they surround the same vertex and are joined by edges that are not hard.
+    /// Without any hard edges this gives the same normals as `vertex_normals` on closed surfaces
+    pub fn try_corner_normals(&self, weighting: HXANormalWeighting, hard_edges: HXAHardEdges) -> Option<Vec<[f32; 3]>> {
+        let positions = self.positions()?;
+        let references = self.corner_stack.find(hard::CORNER_LAYER_NAME)?.try_as_vec_i32()?;
+        let polygons: Vec<HXAPolygon> = self.try_polygons()?.collect();
+
+        let mut faces = vec![0usize; references.len()];
+        let mut next_corners = vec![0usize; references.len()];
+        let mut face_normals = Vec::with_capacity(polygons.len());
+        let mut contributions = vec![[0.0f64; 3]; references.len()];
+
+        for polygon in &polygons {
+            let normal = polygon_normal(polygon, &positions);
+            for corner in polygon.corners.clone() {
+                faces[corner] = polygon.face as usize;
+                next_corners[corner] = if corner + 1 == polygon.corners.end { polygon.corners.start } else { corner + 1 };
+            }
+            for (corner, weight) in corner_weights(polygon, &positions, normal, weighting) {
+                add(&mut contributions[polygon.corners.start + corner], normal, weight);
+            }
+            face_normals.push(normalize(normal));
+        }
+
+        let creases = if hard_edges.creases { self.creases() } else { None };
+        let min_cos = hard_edges.angle.map(|angle| angle.to_radians().cos());
+
+        // Corners around the same vertex are grouped across every edge that is not hard
+        let mut groups = CornerGroups::new(references.len());
+        for (corner, opposite) in compute_neighbours(references).into_iter().enumerate() {
+            // Each shared edge is seen from both sides, only look at it once
+            if opposite < 0 || (opposite as usize) < corner {
+                continue;
+            }
+            let opposite = opposite as usize;
+
+            // A reference layer longer than `edge_corner_count` has corners without a crease value
+            let creased = |corner: usize| creases.as_ref().and_then(|creases| creases.get(corner)).is_some_and(|crease| *crease > 0.0);
+            let is_crease = creased(corner) || creased(opposite);
+            let is_sharp = min_cos.is_some_and(|min_cos| dot(face_normals[faces[corner]], face_normals[faces[opposite]]) < min_cos);
+            if is_crease || is_sharp {
+                continue;
+            }
+
+            // The edge of `corner` runs the opposite way to the edge of `opposite`, so their starts meet the other's next corner
+            groups.join(corner, next_corners[opposite]);
+            groups.join(next_corners[corner], opposite);
+        }
+
+        let mut sums = vec![[0.0f64; 3]; references.len()];
+        for (corner, contribution) in contributions.iter().enumerate() {
+            add(&mut sums[groups.find(corner)], *contribution, 1.0);
+        }
+
+        Some((0..references.len()).map(|corner| to_f32(normalize(sums[groups.find(corner)]))).collect())
+    }
+
+    /// Normals for each corner, smooth across edges except `hard_edges`
+    /// # Panics
+    /// Panics if the `reference` or `vertex` layer is missing
+    pub fn corner_normals(&self, weighting: HXANormalWeighting, hard_edges: HXAHardEdges) -> Vec<[f32; 3]> {
+        self.try_corner_normals(weighting, hard_edges).expect("Expected a reference and a vertex layer")
+    }
+
+    /// Computes smooth vertex normals and stores them as the `normal` layer of the `vertex_stack`
+    ///
+    /// Any existing `normal` layer in the `vertex_stack` or `corner_stack` is replaced
+    pub fn set_vertex_normals(&mut self, weighting: HXANormalWeighting) -> Result<(), HXAError> {
+        let normals = self.try_vertex_normals(weighting).ok_or_else(|| self.missing_layer())?;
+        self.corner_stack.remove(soft::LAYER_NORMALS);
+        replace_normals(&mut self.vertex_stack, normals);
+        Ok(())
+    }
+
+    /// Computes corner normals and stores them as the `normal` layer of the `corner_stack`
+    ///
+    /// Any existing `normal` layer in the `vertex_stack` or `corner_stack` is replaced
+    /// # Example
+    /// ```rust,no_run
+    /// use hxa::enums::HXANodeType;
+    /// use hxa::normals::{HXAHardEdges, HXANormalWeighting};
+    ///
+    /// let mut my_hxa = hxa::HXAFile::from("Cube.hxa");
+    /// for node in &mut my_hxa.node_array {
+    ///     if let HXANodeType::Geometry(geometry) = &mut node.node_type {
+    ///         let hard_edges = HXAHardEdges { creases: true, angle: Some(30.0) };
+    ///         geometry.set_corner_normals(HXANormalWeighting::Angle, hard_edges).expect("Expected a reference and a vertex layer");
+    ///     }
+    /// }
+    /// my_hxa.save("Cube.hxa").expect("Expected to write the file");
+    /// ```
+    pub fn set_corner_normals(&mut self, weighting: HXANormalWeighting, hard_edges: HXAHardEdges) -> Result<(), HXAError> {
+        let references = self.corner_stack.find(hard::CORNER_LAYER_NAME).and_then(|layer| layer.try_as_vec_i32());
+        if let Some(references) = references.filter(|references| references.len() != self.edge_corner_count as usize) {
+            return Err(HXAError::InvalidLayer {
+                layer: String::from(hard::CORNER_LAYER_NAME),
+                reason: format!("expected {} values but it holds {}", self.edge_corner_count, references.len()),
+            });
+        }
+
+        let normals = self.try_corner_normals(weighting, hard_edges).ok_or_else(|| self.missing_layer())?;
+
+        self.vertex_stack.remove(soft::LAYER_NORMALS);
+        replace_normals(&mut self.corner_stack, normals);
+        Ok(())
+    }
+
+    /// The `creases` edge layer as doubles, `None` if it is missing or not a single `FLOAT` or `DOUBLE` per edge
+    fn creases(&self) -> Option<Vec<f64>> {
+        let layer = self.edge_stack.find(soft::LAYER_CREASES)?;
+        let creases: Vec<f64> = match &layer.layer_type {
+            HXALayerDataType::FLOAT(values) => values.iter().map(|value| *value as f64).collect(),
+            HXALayerDataType::DOUBLE(values) => values.clone(),
+            _ => return None,
+        };
+        (layer.components == 1 && creases.len() == self.edge_corner_count as usize).then_some(creases)
+    }
+}
+
+fn replace_normals(stack: &mut HXALayerStack, normals: Vec<[f32; 3]>) {
+    stack.remove(soft::LAYER_NORMALS);
+    stack.push(HXALayer {
+        name: String::from(soft::LAYER_NORMALS),
+        components: 3,
+        layer_type: HXALayerDataType::FLOAT(normals.into_iter().flatten().collect()),
+    });
+}
+
+/// Union find over corners, each group ends up with one representative corner
+struct CornerGroups {
+    parents: Vec<usize>,
+}
+
+impl CornerGroups {
+    fn new(count: usize) -> Self {
+        CornerGroups {
+            parents: (0..count).collect(),
+        }
+    }
+
+    fn find(&mut self, mut corner: usize) -> usize {
+        while self.parents[corner] != corner {
+            self.parents[corner] = self.parents[self.parents[corner]];
+            corner = self.parents[corner];
+        }
+        corner
+    }
+
+    fn join(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        self.parents[a] = b;
+    }
+}
+
+/// The normal of a polygon scaled by twice its area, zero if it refers to missing vertices
+fn polygon_normal(polygon: &HXAPolygon, positions: &[[f64; 3]]) -> [f64; 3] {
+    corner_positions(polygon, positions).map(|corners| newell(&corners)).unwrap_or_default()
+}
+
+/// How much `normal` counts towards each corner of the polygon, given as the corner within the polygon
+fn corner_weights(polygon: &HXAPolygon, positions: &[[f64; 3]], normal: [f64; 3], weighting: HXANormalWeighting) -> Vec<(usize, f64)> {
+    match weighting {
+        // The length of the Newell normal already grows with the area
+        HXANormalWeighting::Area => (0..polygon.len()).map(|corner| (corner, 1.0)).collect(),
+        HXANormalWeighting::Angle => {
+            let length = dot(normal, normal).sqrt();
+            (0..polygon.len()).map(|corner| {
+                let position = |corner: usize| positions.get(polygon.vertex(corner) as usize).copied().unwrap_or_default();
+                let current = position(corner);
+                let previous = sub(position((corner + polygon.len() - 1) % polygon.len()), current);
+                let next = sub(position((corner + 1) % polygon.len()), current);
+
+                let lengths = (dot(previous, previous) * dot(next, next)).sqrt();
+                let angle = if lengths > 0.0 { (dot(previous, next) / lengths).clamp(-1.0, 1.0).acos() } else { 0.0 };
+                (corner, if length > 0.0 { angle / length } else { 0.0 })
+            }).collect()
+        },
+    }
+}
+
+fn add(sum: &mut [f64; 3], vector: [f64; 3], weight: f64) {
+    for axis in 0..3 {
+        sum[axis] += vector[axis] * weight;
+    }
+}
+
+fn sub(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn dot(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+/// Scales the vector to a length of 1, zero vectors stay zero
+fn normalize(vector: [f64; 3]) -> [f64; 3] {
+    let length = dot(vector, vector).sqrt();
+    if length > 0.0 {
+        [vector[0] / length, vector[1] / length, vector[2] / length]
+    } else {
+        [0.0; 3]
+    }
+}
+
+fn to_f32(vector: [f64; 3]) -> [f32; 3] {
+    [vector[0] as f32, vector[1] as f32, vector[2] as f32]
+}
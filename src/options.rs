@@ -0,0 +1,155 @@
+use crate::layer::HXALayer;
+use std::fmt;
+use std::sync::Arc;
+
+/// Settings and limits used while loading a file
+///
+/// Counts stored in a HxA file drive loops and allocations, these limits keep a crafted file from
+/// exhausting memory or the stack. Payload sizes are also checked against the remaining input
+/// before allocating whenever the length of the input is known, as it is for files and byte slices.
+/// Payloads from readers of unknown length are read in pieces that grow as the data arrives.
+/// # Example
+/// ```rust
+/// let options = hxa::LoadOptions {
+///     max_total_bytes: 64 * 1024 * 1024,
+///     ..Default::default()
+/// };
+/// let bytes = [0x48, 0x78, 0x41, 0x00, 3, 0, 0, 0, 0];
+/// let my_hxa = hxa::HXAFile::from_bytes_with(&bytes, options).expect("Expected a valid file");
+/// ```
+#[derive(Debug, Clone)]
+pub struct LoadOptions {
+    /// Most bytes the layer, meta data and text payloads may take in memory, in total
+    pub max_total_bytes: u64,
+
+    /// Deepest nesting of `META` meta data inside other `META` meta data
+    pub max_nesting_depth: u32,
+
+    /// Largest `node_count` accepted
+    pub max_nodes: u32,
+
+    /// Largest `layer_count` accepted for a single layer stack
+    pub max_layers: u32,
+
+    /// Keep nodes of unknown type instead of failing, with a `HXAWarning` for each
+    /// 
+    /// An unknown final node is kept as `HXANodeType::Unsupported` and written back unchanged.
+    /// An unknown node followed by other nodes cannot be measured, so loading stops before it when the
+    /// warnings are returned, as by `HXAFile::open_lenient`, and fails with `HXAError::UnknownNodeType` otherwise
+    pub keep_unknown_nodes: bool,
+
+    /// Which nodes and layers to load, by default everything is loaded
+    pub filter: LoadFilter,
+}
+
+impl LoadOptions {
+    pub fn new() -> Self {
+        LoadOptions {
+            max_total_bytes: u64::MAX,
+            max_nesting_depth: 64,
+            max_nodes: u32::MAX,
+            max_layers: u32::MAX,
+            keep_unknown_nodes: false,
+            filter: LoadFilter::new(),
+        }
+    }
+}
+
+impl Default for LoadOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Decides whether a layer is loaded, see `LoadFilter::layer_predicate`
+pub type LayerPredicate = Arc<dyn Fn(&HXALayer) -> bool + Send + Sync>;
+
+/// Picks the nodes and layers to load, the payloads of everything else are skipped over without being allocated
+///
+/// Every filter that is set has to accept a node or layer for it to be loaded.
+/// Nodes that are filtered out are left out of `node_array`, so `NODE` meta data still refers to the indices in the file.
+/// Layers that are filtered out are left out of their stack.
+/// # Example
+/// ```rust
+/// let options = hxa::LoadOptions {
+///     filter: hxa::LoadFilter {
+///         layer_names: Some(vec![String::from("vertex"), String::from("reference")]),
+///         ..Default::default()
+///     },
+///     ..Default::default()
+/// };
+/// let bytes = [0x48, 0x78, 0x41, 0x00, 3, 0, 0, 0, 0];
+/// let my_hxa = hxa::HXAFile::from_bytes_with(&bytes, options).expect("Expected a valid file");
+/// ```
+#[derive(Clone)]
+pub struct LoadFilter {
+    /// Indices of the nodes to load, as stored in the file
+    pub node_indices: Option<Vec<u32>>,
+
+    /// Types of the nodes to load, see `HXANodeType::type_code`
+    pub node_types: Option<Vec<u8>>,
+
+    /// Names of the layers to load, in every stack
+    pub layer_names: Option<Vec<String>>,
+
+    /// Decides on a layer after its name, components and type are read, its data is still empty at that point
+    pub layer_predicate: Option<LayerPredicate>,
+}
+
+impl LoadFilter {
+    pub fn new() -> Self {
+        LoadFilter {
+            node_indices: None,
+            node_types: None,
+            layer_names: None,
+            layer_predicate: None,
+        }
+    }
+
+    /// Checks the node at `node_index` in the file against `node_indices`
+    pub fn wants_node_index(&self, node_index: u32) -> bool {
+        match &self.node_indices {
+            Some(node_indices) => node_indices.contains(&node_index),
+            None => true,
+        }
+    }
+
+    /// Checks a node type against `node_types`
+    pub fn wants_node_type(&self, type_code: u8) -> bool {
+        match &self.node_types {
+            Some(node_types) => node_types.contains(&type_code),
+            None => true,
+        }
+    }
+
+    /// Checks a layer against `layer_names` and `layer_predicate`
+    pub fn wants_layer(&self, layer: &HXALayer) -> bool {
+        if let Some(layer_names) = &self.layer_names {
+            if !layer_names.contains(&layer.name) {
+                return false;
+            }
+        }
+
+        match &self.layer_predicate {
+            Some(layer_predicate) => layer_predicate(layer),
+            None => true,
+        }
+    }
+}
+
+impl Default for LoadFilter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for LoadFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LoadFilter")
+            .field("node_indices", &self.node_indices)
+            .field("node_types", &self.node_types)
+            .field("layer_names", &self.layer_names)
+            .field("layer_predicate", &self.layer_predicate.as_ref().map(|_| "Fn(&HXALayer) -> bool"))
+            .finish()
+    }
+}
@@ -0,0 +1,307 @@
+use crate::enums::HXANodeType;
+use crate::error::{HXAError, HXAWarning};
+use crate::hxfile::read_preamble;
+use crate::node::HXANode;
+use crate::options::LoadOptions;
+use crate::reader::HXAReader;
+use std::io::{Cursor, Read};
+
+/// Parses a HxA file pushed to it in chunks, handing back each node as soon as all of its bytes have arrived
+///
+/// Only the bytes of the node being received are kept, nodes are decoded with `HXANode::parse` like any other load.
+/// Each time a chunk arrives the parser walks on through the structure of the pending node while skipping its layer data,
+/// starting after the last meta data entry it completed, and decodes the node in full once that walk reaches its end.
+/// Every byte of meta data is thus read twice at most, however the node is split into chunks.
+/// # Example
+/// ```rust
+/// let bytes = [0x48, 0x78, 0x41, 0x00, 3, 1, 0, 0, 0, 0, 0, 0, 0, 0];
+/// let mut parser = hxa::HXAParser::new();
+/// let mut nodes = Vec::new();
+/// for chunk in bytes.chunks(3) {
+///     nodes.extend(parser.feed(chunk).expect("Expected a valid file"));
+/// }
+/// parser.finish().expect("Expected the file to be complete");
+/// assert_eq!(nodes.len(), 1);
+/// ```
+#[derive(Debug)]
+pub struct HXAParser {
+    options: LoadOptions,
+
+    /// Bytes received that are not part of a node handed back yet
+    buffer: Vec<u8>,
+
+    /// Bytes of the file handed back as nodes, or read as the header
+    consumed: u64,
+
+    /// `max_total_bytes` used by the nodes handed back so far
+    allocated: u64,
+
+    version: Option<u8>,
+    node_count: u32,
+
+    /// Index of the node being received
+    node_index: u32,
+
+    /// A final node of unknown type is pending, it runs until the end of the input
+    unsupported_tail: bool,
+
+    /// How far the walk of the node being received got
+    walk: NodeWalk,
+
+    warnings: Vec<HXAWarning>,
+}
+
+impl HXAParser {
+    pub fn new() -> Self {
+        HXAParser::with_options(LoadOptions::default())
+    }
+
+    /// Creates a parser that loads under the given limits and filters
+    pub fn with_options(options: LoadOptions) -> Self {
+        HXAParser {
+            options,
+            buffer: Vec::new(),
+            consumed: 0,
+            allocated: 0,
+            version: None,
+            node_count: 0,
+            node_index: 0,
+            unsupported_tail: false,
+            walk: NodeWalk::new(),
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Adds the next chunk of the file, returns the nodes it completed in file order
+    ///
+    /// Nodes left out by the `LoadFilter` are not returned. Bytes after the last node are ignored.
+    /// `max_total_bytes` covers the payloads of the nodes decoded so far and the bytes of the node still being received,
+    /// so the buffer for a node that has not fully arrived cannot grow past it
+    pub fn feed(&mut self, chunk: &[u8]) -> Result<Vec<HXANode>, HXAError> {
+        let mut nodes = Vec::new();
+        if self.is_complete() {
+            return Ok(nodes);
+        }
+
+        self.buffer.extend_from_slice(chunk);
+        if self.version.is_none() && !self.read_header()? {
+            return Ok(nodes);
+        }
+
+        while !self.is_complete() && !self.unsupported_tail {
+            let node_length = match self.measure_node()? {
+                Some(node_length) => node_length,
+                None => break,
+            };
+            if self.unsupported_tail {
+                break;
+            }
+
+            if let Some(node) = self.decode_node(node_length)? {
+                nodes.push(node);
+            }
+        }
+        if self.is_complete() {
+            self.buffer = Vec::new();
+        }
+
+        // Waiting for the rest of the pending node would keep more than the limit in memory
+        let budget = self.options.max_total_bytes.saturating_sub(self.allocated);
+        if self.buffer.len() as u64 > budget {
+            return Err(HXAError::LimitExceeded {
+                limit: "max_total_bytes",
+                value: self.allocated.saturating_add(self.buffer.len() as u64),
+                max: self.options.max_total_bytes,
+            }.at(self.bytes_fed()));
+        }
+
+        Ok(nodes)
+    }
+
+    /// Ends the input, fails with `HXAError::Truncated` if nodes are still missing
+    ///
+    /// A final node of unknown type kept because of `LoadOptions::keep_unknown_nodes` is returned here,
+    /// as its payload runs until the end of the input
+    pub fn finish(mut self) -> Result<Option<HXANode>, HXAError> {
+        if self.unsupported_tail {
+            let node_length = self.buffer.len();
+            return self.decode_node(node_length);
+        }
+
+        if !self.is_complete() {
+            return Err(HXAError::Truncated.at(self.bytes_fed()));
+        }
+
+        Ok(None)
+    }
+
+    /// Number of bytes fed so far, excluding ignored bytes after the last node
+    pub fn bytes_fed(&self) -> u64 {
+        self.consumed + self.buffer.len() as u64
+    }
+
+    /// Number of nodes fully received so far, including nodes left out by the `LoadFilter`
+    pub fn nodes_parsed(&self) -> u32 {
+        self.node_index
+    }
+
+    /// Number of nodes in the file, once the header has been received
+    pub fn node_count(&self) -> Option<u32> {
+        self.version.map(|_| self.node_count)
+    }
+
+    /// Format version of the file, once the header has been received
+    pub fn version(&self) -> Option<u8> {
+        self.version
+    }
+
+    /// Whether every node of the file has been received
+    pub fn is_complete(&self) -> bool {
+        self.version.is_some() && self.node_index >= self.node_count && !self.unsupported_tail
+    }
+
+    /// Takes the warnings collected so far, leaving none behind
+    pub fn take_warnings(&mut self) -> Vec<HXAWarning> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    /// Reads the header once enough bytes are buffered, returns `false` while they are not
+    fn read_header(&mut self) -> Result<bool, HXAError> {
+        let mut input = HXAReader::with_options(&self.buffer[..], self.options.clone(), Some(self.buffer.len() as u64));
+        let (version, node_count) = match read_preamble(&mut input) {
+            Ok(preamble) => preamble,
+            Err(err) if is_incomplete(&err) => return Ok(false),
+            Err(err) => return Err(err),
+        };
+
+        let header_length = input.position();
+        self.buffer.drain(..header_length as usize);
+        self.consumed = header_length;
+        self.version = Some(version);
+        self.node_count = node_count;
+        Ok(true)
+    }
+
+    /// Walks the buffered node without its layer data, returns its length once all of it is buffered
+    fn measure_node(&mut self) -> Result<Option<usize>, HXAError> {
+        if self.walk.length == 0 {
+            self.walk.allocated = self.allocated;
+        }
+        let walked = self.walk.length;
+        let mut input = HXAReader::seekable(Cursor::new(&self.buffer[walked as usize..]), self.options.clone())?;
+        input.starting_at(self.consumed + walked);
+        input.allocated_before(self.walk.allocated);
+        input.skip_layer_data = true;
+
+        match self.walk.resume(&mut input, self.consumed) {
+            Ok(()) => (),
+            Err(err) if is_incomplete(&err) => return Ok(None),
+            Err(err) => return Err(err.within(format!("node[{}]", self.node_index))),
+        }
+
+        let mut node = std::mem::replace(&mut self.walk, NodeWalk::new()).node;
+        let node_length = input.position() - self.consumed;
+        if !input.finish_unknown_node(&mut node, self.node_index, self.node_count, self.consumed)? {
+            self.warnings.extend(input.take_warnings());
+            // The length of the node is unknown so nothing after it can be read
+            self.node_index = self.node_count;
+            self.buffer = Vec::new();
+            return Ok(None);
+        }
+        // A final unknown node is decoded by `finish` once the input has ended
+        self.unsupported_tail = matches!(node.node_type, HXANodeType::Unsupported { .. });
+
+        Ok(Some(node_length as usize))
+    }
+
+    /// Parses the first `node_length` buffered bytes as a node and drops them from the buffer
+    fn decode_node(&mut self, node_length: usize) -> Result<Option<HXANode>, HXAError> {
+        let node_offset = self.consumed;
+        let bytes = &self.buffer[..node_length];
+        let mut input = HXAReader::with_options(bytes, self.options.clone(), Some(node_length as u64));
+        input.starting_at(node_offset);
+        input.allocated_before(self.allocated);
+        input.skip_node = !self.options.filter.wants_node_index(self.node_index);
+
+        let mut node = HXANode::new();
+        node.parse(&mut input).map_err(|err| err.within(format!("node[{}]", self.node_index)))?;
+
+        input.finish_unknown_node(&mut node, self.node_index, self.node_count, node_offset)?;
+        self.unsupported_tail = false;
+
+        self.allocated = input.allocated();
+        let skip_node = input.skip_node;
+        self.warnings.extend(input.take_warnings());
+
+        self.buffer.drain(..node_length);
+        self.consumed += node_length as u64;
+        self.node_index += 1;
+
+        if skip_node {
+            return Ok(None);
+        }
+        Ok(Some(node))
+    }
+}
+
+impl Default for HXAParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The walk through the structure of a node that has not fully arrived, kept between chunks
+///
+/// The walk is only saved after whole meta data entries, payloads are walked from their start each time
+/// as their layer data is skipped over anyway
+#[derive(Debug)]
+struct NodeWalk {
+    /// The node type and meta data count once read, the meta data is only walked and not kept
+    node: HXANode,
+
+    /// Meta data entries walked so far
+    metas_walked: u32,
+
+    /// Bytes of the node walked so far, up to the end of the header or the last meta data entry walked
+    length: u64,
+
+    /// `max_total_bytes` used up to that point
+    allocated: u64,
+}
+
+impl NodeWalk {
+    fn new() -> Self {
+        NodeWalk {
+            node: HXANode::new(),
+            metas_walked: 0,
+            length: 0,
+            allocated: 0,
+        }
+    }
+
+    /// Walks on from where the last walk stopped, `input` has to start `length` bytes into the node at `node_offset`
+    fn resume<R: Read>(&mut self, input: &mut HXAReader<R>, node_offset: u64) -> Result<(), HXAError> {
+        if self.length == 0 {
+            self.node.parse_header(input)?;
+            self.save(input, node_offset);
+        }
+
+        while self.metas_walked < self.node.metadata_count {
+            HXANode::parse_meta(input, self.metas_walked)?;
+            self.metas_walked += 1;
+            self.save(input, node_offset);
+        }
+
+        self.node.parse_payload(input)
+    }
+
+    fn save<R: Read>(&mut self, input: &HXAReader<R>, node_offset: u64) {
+        self.length = input.position() - node_offset;
+        self.allocated = input.allocated();
+    }
+}
+
+/// Whether parsing failed only because the rest of the node has not arrived yet
+fn is_incomplete(err: &HXAError) -> bool {
+    matches!(err.root(), HXAError::Truncated)
+}
@@ -0,0 +1,124 @@
+use crate::conventions::hard;
+use crate::node::HXAGeometryNode;
+use std::ops::Range;
+
+/// Decodes a value of the `reference` layer into a vertex index
+///
+/// The last corner of each polygon stores `-index - 1` so the end of the polygon can be found
+pub fn decode_reference(reference: i32) -> u32 {
+    if reference < 0 {
+        // Same as `-reference - 1` without overflowing on `i32::MIN`
+        !reference as u32
+    } else {
+        reference as u32
+    }
+}
+
+/// One polygon of a geometry node, see `HXAGeometryNode::polygons`
+#[derive(Debug, Clone)]
+pub struct HXAPolygon<'a> {
+    /// Index of the polygon, used to look up values in the `face_stack`
+    pub face: u32,
+
+    /// Indices of the corners of the polygon, used to look up values in the `corner_stack` and `edge_stack`
+    pub corners: Range<usize>,
+
+    references: &'a [i32],
+}
+
+impl<'a> HXAPolygon<'a> {
+    /// Number of corners
+    pub fn len(&self) -> usize {
+        self.corners.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.corners.is_empty()
+    }
+
+    /// The vertex index of each corner, decoded from the `reference` layer
+    pub fn vertices(&self) -> impl ExactSizeIterator<Item = u32> + 'a {
+        self.references[self.corners.clone()].iter().map(|reference| decode_reference(*reference))
+    }
+
+    /// The vertex index of the corner at `corner` within the polygon
+    /// # Panics
+    /// Panics if `corner` is not below `len`
+    pub fn vertex(&self, corner: usize) -> u32 {
+        decode_reference(self.references[self.corners.start + corner])
+    }
+
+    /// Whether the last corner ends the polygon with a negative reference, only the final polygon of a broken layer is open
+    pub fn is_closed(&self) -> bool {
+        self.corners.end > self.corners.start && self.references[self.corners.end - 1] < 0
+    }
+}
+
+/// Iterator over the polygons of a geometry node, see `HXAGeometryNode::polygons`
+#[derive(Debug, Clone)]
+pub struct HXAPolygons<'a> {
+    references: &'a [i32],
+    next_corner: usize,
+    next_face: u32,
+}
+
+impl<'a> HXAPolygons<'a> {
+    /// Iterates over the polygons encoded in the values of a `reference` layer
+    pub fn new(references: &'a [i32]) -> Self {
+        HXAPolygons {
+            references,
+            next_corner: 0,
+            next_face: 0,
+        }
+    }
+}
+
+impl<'a> Iterator for HXAPolygons<'a> {
+    type Item = HXAPolygon<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next_corner >= self.references.len() {
+            return None;
+        }
+
+        let start = self.next_corner;
+        // A layer that does not end in a negative reference yields its last corners as an open polygon
+        let end = match self.references[start..].iter().position(|reference| *reference < 0) {
+            Some(last) => start + last + 1,
+            None => self.references.len(),
+        };
+
+        let polygon = HXAPolygon {
+            face: self.next_face,
+            corners: start..end,
+            references: self.references,
+        };
+        self.next_corner = end;
+        self.next_face += 1;
+        Some(polygon)
+    }
+}
+
+impl HXAGeometryNode {
+    /// Iterates over the polygons, `None` if there is no `INT32` `reference` layer
+    pub fn try_polygons(&self) -> Option<HXAPolygons<'_>> {
+        let references = self.corner_stack.find(hard::CORNER_LAYER_NAME)?.try_as_vec_i32()?;
+        Some(HXAPolygons::new(references))
+    }
+
+    /// Iterates over the polygons, decoding the `reference` layer
+    /// # Panics
+    /// Panics if there is no `reference` layer or it is not `INT32`
+    /// # Example
+    /// ```rust,no_run
+    /// let my_hxa = hxa::HXAFile::from("Cube.hxa");
+    /// let geometry = my_hxa.get_first_geometry().expect("Expected to find a geometry node").0;
+    /// for polygon in geometry.polygons() {
+    ///     let vertices: Vec<u32> = polygon.vertices().collect();
+    ///     println!("face {} uses corners {:?} and vertices {:?}", polygon.face, polygon.corners, vertices);
+    /// }
+    /// ```
+    pub fn polygons(&self) -> HXAPolygons<'_> {
+        self.try_polygons().expect("Expected an INT32 reference layer")
+    }
+}
@@ -0,0 +1,80 @@
+use std::fmt;
+use std::io::{self, Write};
+
+/// How far a load or save has come, passed to a `HXAObserver`
+#[derive(Debug, Clone, Copy)]
+pub struct HXAProgress {
+    /// Bytes read or written so far
+    pub bytes: u64,
+
+    /// Size of the whole file, when known
+    pub total_bytes: Option<u64>,
+
+    /// Nodes read or written completely
+    pub nodes_done: u32,
+
+    /// Number of nodes in the file, 0 until the header has been read
+    pub node_count: u32,
+}
+
+impl HXAProgress {
+    /// Fraction of the file done from 0 to 1, by bytes when the size is known and otherwise by nodes
+    pub fn fraction(&self) -> f32 {
+        match self.total_bytes {
+            Some(0) => 1.0,
+            Some(total_bytes) => self.bytes as f32 / total_bytes as f32,
+            None if self.node_count == 0 => 0.0,
+            None => self.nodes_done as f32 / self.node_count as f32,
+        }
+    }
+}
+
+/// Receives progress while a file is loaded or saved and can cancel it
+///
+/// Loads report after every layer and every node, saves after every node.
+/// Any `FnMut(&HXAProgress) -> bool` closure is an observer
+/// # Example
+/// ```rust,no_run
+/// let observer = |progress: &hxa::HXAProgress| {
+///     println!("{:.0}%", progress.fraction() * 100.0);
+///     true
+/// };
+/// let my_hxa = hxa::HXAFile::open_observed("Scan.hxa", hxa::LoadOptions::default(), observer);
+/// ```
+pub trait HXAObserver {
+    /// Called as the load or save moves forward, returning `false` stops it with `HXAError::Cancelled`
+    fn progress(&mut self, progress: &HXAProgress) -> bool;
+}
+
+impl<F: FnMut(&HXAProgress) -> bool> HXAObserver for F {
+    fn progress(&mut self, progress: &HXAProgress) -> bool {
+        self(progress)
+    }
+}
+
+/// An observer kept by the reader, so it can be called from any parse function
+pub(crate) struct BoxedObserver(pub(crate) Box<dyn HXAObserver>);
+
+impl fmt::Debug for BoxedObserver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("HXAObserver")
+    }
+}
+
+/// Counts the bytes going through a writer, for reporting save progress
+pub(crate) struct CountingWriter<W: Write> {
+    pub(crate) inner: W,
+    pub(crate) written: u64,
+}
+
+impl<W: Write> Write for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.written += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
@@ -0,0 +1,354 @@
+use crate::enums::HXANodeType;
+use crate::error::{HXAError, HXAWarning};
+use crate::node::HXANode;
+use crate::options::LoadOptions;
+use crate::progress::{BoxedObserver, HXAObserver, HXAProgress};
+use std::io::{self, Read, Seek, SeekFrom};
+
+/// Wraps any `Read` source and keeps track of how many bytes have been consumed
+///
+/// All the parse functions read through this so errors can report the byte offset where they happened
+/// without needing the source to be `Seek`. It also carries the `LoadOptions` the parse functions check against.
+/// # Example
+/// ```rust
+/// let bytes: &[u8] = &[0x48, 0x78, 0x41, 0x00];
+/// let mut input = hxa::HXAReader::new(bytes);
+/// ```
+#[derive(Debug)]
+pub struct HXAReader<R: Read> {
+    inner: R,
+    position: u64,
+
+    /// Total length of the input, when known
+    length: Option<u64>,
+    options: LoadOptions,
+
+    /// Bytes reserved for payloads so far
+    allocated: u64,
+
+    /// Current `META` nesting depth
+    depth: u32,
+
+    warnings: Vec<HXAWarning>,
+
+    /// Moves the underlying reader forward without reading, for sources that can seek
+    seek_forward: Option<fn(&mut R, u64) -> io::Result<()>>,
+
+    /// Skip over layer payloads instead of reading them, used to summarize files
+    pub(crate) skip_layer_data: bool,
+
+    /// The node being read was filtered out by the `LoadFilter`, so none of its layers are kept
+    pub(crate) skip_node: bool,
+
+    observer: Option<BoxedObserver>,
+    nodes_done: u32,
+    node_count: u32,
+}
+
+impl<R: Read> HXAReader<R> {
+    pub fn new(inner: R) -> Self {
+        HXAReader::with_options(inner, LoadOptions::default(), None)
+    }
+
+    /// Wraps a reader with limits to load under
+    ///
+    /// `length` is the total size of the input, if known it is used to reject sizes that cannot fit in what is left
+    pub fn with_options(inner: R, options: LoadOptions, length: Option<u64>) -> Self {
+        HXAReader {
+            inner,
+            position: 0,
+            length,
+            options,
+            allocated: 0,
+            depth: 0,
+            warnings: Vec::new(),
+            seek_forward: None,
+            skip_layer_data: false,
+            skip_node: false,
+            observer: None,
+            nodes_done: 0,
+            node_count: 0,
+        }
+    }
+
+    /// Number of bytes consumed from the underlying reader
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Number of bytes left in the input, if its length is known
+    pub fn remaining(&self) -> Option<u64> {
+        self.length.map(|length| length.saturating_sub(self.position))
+    }
+
+    pub fn options(&self) -> &LoadOptions {
+        &self.options
+    }
+
+    /// Problems that were tolerated so far because of the `LoadOptions`
+    pub fn warnings(&self) -> &[HXAWarning] {
+        &self.warnings
+    }
+
+    /// Takes the warnings collected so far, leaving none behind
+    pub fn take_warnings(&mut self) -> Vec<HXAWarning> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    pub(crate) fn warn(&mut self, warning: HXAWarning) {
+        self.warnings.push(warning);
+    }
+
+    /// Reads everything left in the input, within `max_total_bytes`
+    pub(crate) fn read_rest(&mut self) -> Result<Vec<u8>, HXAError> {
+        let mut rest = Vec::new();
+        let limit = match self.remaining() {
+            Some(remaining) => {
+                self.reserve(remaining)?;
+                rest.reserve_exact(remaining as usize);
+                remaining
+            },
+            // One byte over the budget is enough to tell the limit was exceeded
+            None => self.options.max_total_bytes.saturating_sub(self.allocated).saturating_add(1),
+        };
+
+        if let Err(err) = self.by_ref().take(limit).read_to_end(&mut rest) {
+            return Err(HXAError::from(err).at(self.position));
+        }
+        if self.length.is_none() {
+            self.reserve(rest.len() as u64)?;
+        }
+
+        Ok(rest)
+    }
+
+    /// Deals with a node `HXANode::parse` returned as `HXANodeType::Unsupported`, returns `false` if loading has to stop before it
+    ///
+    /// Only a final unknown node can be kept, its payload runs to the end of the input and is read, or skipped over
+    /// when `skip_layer_data` is set. An unknown node followed by other nodes cannot be measured, so it is recorded as
+    /// `UnknownNodeSkipped` along with the nodes after it. Other nodes are left alone
+    pub(crate) fn finish_unknown_node(&mut self, node: &mut HXANode, node_index: u32, node_count: u32, offset: u64) -> Result<bool, HXAError> {
+        let (type_code, payload) = match &mut node.node_type {
+            HXANodeType::Unsupported { type_code, payload } => (*type_code, payload),
+            _ => return Ok(true),
+        };
+
+        let nodes_after = node_count - node_index - 1;
+        if nodes_after > 0 {
+            self.warn(HXAWarning::UnknownNodeSkipped { node_index, type_code, offset, nodes_after });
+            return Ok(false);
+        }
+
+        let payload_length = if self.skip_layer_data {
+            let payload_length = self.remaining().unwrap_or_default();
+            self.skip(payload_length).map_err(|err| err.within(format!("node[{}]", node_index)))?;
+            payload_length as usize
+        } else {
+            *payload = self.read_rest().map_err(|err| err.within(format!("node[{}]", node_index)))?;
+            payload.len()
+        };
+        self.warn(HXAWarning::UnknownNodeKept { node_index, type_code, offset, payload_length });
+
+        Ok(true)
+    }
+
+    /// Reports progress to `observer` while parsing, which can cancel the load
+    pub fn set_observer<O: HXAObserver + 'static>(&mut self, observer: O) {
+        self.observer = Some(BoxedObserver(Box::new(observer)));
+    }
+
+    /// Tells the observer how far the load has come, fails with `HXAError::Cancelled` if it asks to stop
+    pub(crate) fn report(&mut self) -> Result<(), HXAError> {
+        if let Some(observer) = &mut self.observer {
+            let progress = HXAProgress {
+                bytes: self.position,
+                total_bytes: self.length,
+                nodes_done: self.nodes_done,
+                node_count: self.node_count,
+            };
+            if !observer.0.progress(&progress) {
+                return Err(HXAError::Cancelled.at(self.position));
+            }
+        }
+        Ok(())
+    }
+
+    /// Records that `nodes_done` of `node_count` nodes were read and reports it
+    pub(crate) fn report_nodes(&mut self, nodes_done: u32, node_count: u32) -> Result<(), HXAError> {
+        self.nodes_done = nodes_done;
+        self.node_count = node_count;
+        self.report()
+    }
+
+    /// Returns the underlying reader
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Reads exactly `bytes` bytes of payload, within `max_total_bytes`
+    ///
+    /// When the length of the input is unknown the buffer grows as the data arrives rather than being sized
+    /// from the count in the file, so a count that claims more data than there is fails as `Truncated`
+    /// without allocating it
+    pub(crate) fn read_payload(&mut self, bytes: u64) -> Result<Vec<u8>, HXAError> {
+        self.reserve(bytes)?;
+
+        let mut payload = Vec::new();
+        if self.length.is_some() {
+            // `reserve` checked that the payload fits in what is left of the input
+            payload.resize(bytes as usize, 0);
+            if let Err(err) = self.read_exact(&mut payload) {
+                return Err(HXAError::from(err).at(self.position));
+            }
+        } else {
+            if let Err(err) = self.by_ref().take(bytes).read_to_end(&mut payload) {
+                return Err(HXAError::from(err).at(self.position));
+            }
+            if (payload.len() as u64) < bytes {
+                return Err(HXAError::Truncated.at(self.position));
+            }
+        }
+
+        Ok(payload)
+    }
+
+    /// Checks that `bytes` more bytes of payload can be read and kept in memory, before allocating room for them
+    pub(crate) fn reserve(&mut self, bytes: u64) -> Result<(), HXAError> {
+        if let (Some(remaining), Some(length)) = (self.remaining(), self.length) {
+            if bytes > remaining {
+                // The payload would run past the end of the input, so fail as if it had been read
+                return Err(HXAError::Truncated.at(length));
+            }
+        }
+
+        self.allocated = self.allocated.saturating_add(bytes);
+        if self.allocated > self.options.max_total_bytes {
+            return Err(HXAError::LimitExceeded {
+                limit: "max_total_bytes",
+                value: self.allocated,
+                max: self.options.max_total_bytes,
+            }.at(self.position));
+        }
+
+        Ok(())
+    }
+
+    /// Checks a count read from the file against one of the limits
+    pub(crate) fn check_count(&self, limit: &'static str, value: u32, max: u32) -> Result<(), HXAError> {
+        if value > max {
+            // The count is the 4 bytes just read
+            return Err(HXAError::LimitExceeded {
+                limit,
+                value: value as u64,
+                max: max as u64,
+            }.at(self.position - 4));
+        }
+        Ok(())
+    }
+
+    /// Enters a nested `META` meta data, failing if it goes deeper than `max_nesting_depth`
+    pub(crate) fn enter(&mut self) -> Result<(), HXAError> {
+        self.depth += 1;
+        if self.depth > self.options.max_nesting_depth {
+            return Err(HXAError::LimitExceeded {
+                limit: "max_nesting_depth",
+                value: self.depth as u64,
+                max: self.options.max_nesting_depth as u64,
+            }.at(self.position));
+        }
+        Ok(())
+    }
+
+    /// Leaves a nested `META` meta data entered with `enter`
+    pub(crate) fn leave(&mut self) {
+        self.depth -= 1;
+    }
+
+    /// Bytes reserved for payloads so far, see `max_total_bytes`
+    pub(crate) fn allocated(&self) -> u64 {
+        self.allocated
+    }
+
+    /// Continues the `max_total_bytes` budget of readers that came before this one
+    pub(crate) fn allocated_before(&mut self, bytes: u64) {
+        self.allocated = bytes;
+    }
+
+    /// Counts positions from `position` rather than 0, for readers placed in the middle of a file
+    pub(crate) fn starting_at(&mut self, position: u64) {
+        self.position += position;
+        self.length = self.length.map(|length| length + position);
+    }
+
+    /// Moves past `bytes` bytes of payload without keeping them, seeking when the source allows it
+    pub(crate) fn skip(&mut self, bytes: u64) -> Result<(), HXAError> {
+        if let (Some(remaining), Some(length)) = (self.remaining(), self.length) {
+            if bytes > remaining {
+                self.position = length;
+                return Err(HXAError::Truncated.at(length));
+            }
+        }
+
+        match self.seek_forward {
+            Some(seek_forward) => {
+                if let Err(err) = seek_forward(&mut self.inner, bytes) {
+                    return Err(HXAError::from(err).at(self.position));
+                }
+                self.position += bytes;
+            },
+            None => {
+                let skipped = match io::copy(&mut self.by_ref().take(bytes), &mut io::sink()) {
+                    Ok(skipped) => skipped,
+                    Err(err) => return Err(HXAError::from(err).at(self.position)),
+                };
+                if skipped < bytes {
+                    return Err(HXAError::Truncated.at(self.position));
+                }
+            },
+        }
+
+        Ok(())
+    }
+}
+
+impl<R: Read + Seek> HXAReader<R> {
+    /// Wraps a reader that can seek, so payloads that are not needed are seeked over rather than read
+    ///
+    /// The length of the input is measured from the current position to the end
+    pub fn seekable(mut inner: R, options: LoadOptions) -> Result<Self, HXAError> {
+        let start = inner.stream_position()?;
+        let end = inner.seek(SeekFrom::End(0))?;
+        inner.seek(SeekFrom::Start(start))?;
+
+        let mut reader = HXAReader::with_options(inner, options, Some(end.saturating_sub(start)));
+        reader.seek_forward = Some(|inner: &mut R, bytes: u64| {
+            // Layer payloads are far smaller than i64::MAX as the length is checked before skipping
+            inner.seek(SeekFrom::Current(bytes as i64)).map(|_| ())
+        });
+        Ok(reader)
+    }
+}
+
+impl<R: Read> Read for HXAReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.position += read as u64;
+        Ok(read)
+    }
+}
+
+impl<'a> HXAReader<&'a [u8]> {
+    /// Takes the next `length` bytes out of the slice without copying them
+    pub(crate) fn borrow_bytes(&mut self, length: usize) -> Result<&'a [u8], HXAError> {
+        if self.inner.len() < length {
+            self.position += self.inner.len() as u64;
+            self.inner = &[];
+            return Err(HXAError::Truncated.at(self.position));
+        }
+
+        let (bytes, rest) = self.inner.split_at(length);
+        self.inner = rest;
+        self.position += length as u64;
+        Ok(bytes)
+    }
+}
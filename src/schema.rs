@@ -0,0 +1,259 @@
+use crate::conventions::{hard, soft};
+use crate::enums::{HXALayerValueType, HXANodeType};
+use crate::hxfile::HXAFile;
+use crate::layer::HXALayerStack;
+use crate::validation::HXASeverity;
+use std::fmt;
+
+use HXALayerValueType::{DOUBLE, FLOAT, INT32};
+
+/// The layer stacks of a geometry node
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HXAStack {
+    Vertex,
+    Corner,
+    Edge,
+    Face,
+}
+
+impl HXAStack {
+    /// Name of the stack field in `HXAGeometryNode`, such as `corner_stack`
+    pub fn name(&self) -> &'static str {
+        match self {
+            HXAStack::Vertex => "vertex_stack",
+            HXAStack::Corner => "corner_stack",
+            HXAStack::Edge => "edge_stack",
+            HXAStack::Face => "face_stack",
+        }
+    }
+}
+
+/// What a layer with a given name is expected to look like
+#[derive(Debug, Clone)]
+pub struct HXALayerConvention {
+    pub name: String,
+
+    /// Stacks the layer may be stored in
+    pub stacks: Vec<HXAStack>,
+
+    /// Value types the layer may be stored as
+    pub types: Vec<HXALayerValueType>,
+
+    /// Number of components, `None` when any number is allowed, such as for skinning weights
+    pub components: Option<u8>,
+
+    /// Whether the layer has to be present in the first of its `stacks`
+    pub required: bool,
+
+    /// How serious breaking this convention is
+    pub severity: HXASeverity,
+}
+
+impl HXALayerConvention {
+    /// A convention a layer should follow when it is present, broken conventions are reported as warnings
+    pub fn new(name: &str, stacks: &[HXAStack], types: &[HXALayerValueType], components: Option<u8>) -> Self {
+        HXALayerConvention {
+            name: String::from(name),
+            stacks: stacks.to_vec(),
+            types: types.to_vec(),
+            components,
+            required: false,
+            severity: HXASeverity::Warning,
+        }
+    }
+}
+
+/// A set of layer conventions to check files against
+///
+/// `HXASchema::standard` holds the hard and soft conventions of the format,
+/// studio specific layers can be registered on top of it to make a custom profile.
+/// # Example
+/// ```rust
+/// use hxa::enums::HXALayerValueType;
+/// use hxa::schema::{HXALayerConvention, HXASchema, HXAStack};
+///
+/// let mut profile = HXASchema::standard();
+/// profile.register(HXALayerConvention::new("wetness", &[HXAStack::Vertex], &[HXALayerValueType::FLOAT], Some(1)));
+///
+/// let my_hxa = hxa::HXAFile::new();
+/// assert!(profile.validate(&my_hxa).is_empty());
+/// ```
+#[derive(Debug, Clone)]
+pub struct HXASchema {
+    pub conventions: Vec<HXALayerConvention>,
+}
+
+impl HXASchema {
+    /// A schema without any conventions
+    pub fn new() -> Self {
+        HXASchema {
+            conventions: Vec::new(),
+        }
+    }
+
+    /// The conventions every valid file follows, broken ones are reported as errors
+    pub fn hard() -> Self {
+        let mut schema = HXASchema::new();
+        schema.register(HXALayerConvention {
+            required: true,
+            severity: HXASeverity::Error,
+            ..HXALayerConvention::new(hard::BASE_VERTEX_LAYER_NAME, &[HXAStack::Vertex], &[FLOAT, DOUBLE], Some(hard::VERTEX_LAYER_COMPONENTS as u8))
+        });
+        schema.register(HXALayerConvention {
+            required: true,
+            severity: HXASeverity::Error,
+            ..HXALayerConvention::new(hard::CORNER_LAYER_NAME, &[HXAStack::Corner], &[INT32], Some(hard::CORNER_LAYER_COMPONENTS as u8))
+        });
+        schema.register(HXALayerConvention {
+            severity: HXASeverity::Error,
+            ..HXALayerConvention::new(hard::NEIGHBOUR_LAYER_NAME, &[HXAStack::Edge], &[INT32], Some(1))
+        });
+        schema
+    }
+
+    /// The hard conventions together with the soft conventions, broken soft conventions are reported as warnings
+    pub fn standard() -> Self {
+        let mut schema = HXASchema::hard();
+        let vertex_or_corner = &[HXAStack::Vertex, HXAStack::Corner];
+
+        schema.register(HXALayerConvention::new(soft::LAYER_SEQUENCE0, &[HXAStack::Corner], &[INT32], Some(1)));
+        schema.register(HXALayerConvention::new(soft::LAYER_NAME_UV0, &[HXAStack::Corner, HXAStack::Vertex], &[FLOAT, DOUBLE], Some(2)));
+        schema.register(HXALayerConvention::new(soft::LAYER_NORMALS, vertex_or_corner, &[FLOAT, DOUBLE], Some(3)));
+        schema.register(HXALayerConvention::new(soft::LAYER_TANGENT, vertex_or_corner, &[FLOAT, DOUBLE], Some(3)));
+        schema.register(HXALayerConvention::new(soft::LAYER_CREASES, &[HXAStack::Edge], &[FLOAT, DOUBLE], Some(1)));
+        schema.register(HXALayerConvention::new(
+            soft::LAYER_SELECTION,
+            &[HXAStack::Vertex, HXAStack::Corner, HXAStack::Edge, HXAStack::Face],
+            &[FLOAT, DOUBLE],
+            Some(1)
+        ));
+        schema.register(HXALayerConvention::new(soft::LAYER_SKIN_WEIGHT, &[HXAStack::Vertex], &[FLOAT, DOUBLE], None));
+        schema.register(HXALayerConvention::new(soft::LAYER_SKIN_REFERENCE, &[HXAStack::Vertex], &[INT32], None));
+        schema.register(HXALayerConvention::new(soft::LAYER_BLENDSHAPE, &[HXAStack::Vertex], &[FLOAT, DOUBLE], Some(3)));
+        schema.register(HXALayerConvention::new(soft::LAYER_ADD_BLENDSHAPE, &[HXAStack::Vertex], &[FLOAT, DOUBLE], Some(3)));
+        schema.register(HXALayerConvention::new(soft::LAYER_MATERIAL_ID, &[HXAStack::Face], &[INT32], Some(1)));
+        schema
+    }
+
+    /// Adds a convention, replacing any convention with the same name
+    pub fn register(&mut self, convention: HXALayerConvention) {
+        self.conventions.retain(|existing| existing.name != convention.name);
+        self.conventions.push(convention);
+    }
+
+    /// Finds the convention for layers named `name`
+    pub fn find(&self, name: &str) -> Option<&HXALayerConvention> {
+        self.conventions.iter().find(|convention| convention.name == name)
+    }
+
+    /// Checks every layer of every geometry node in the file against the conventions
+    ///
+    /// Layers without a convention are not checked
+    pub fn validate(&self, file: &HXAFile) -> Vec<HXASchemaIssue> {
+        let mut issues = Vec::new();
+
+        for (node_index, node) in file.node_array.iter().enumerate() {
+            if let HXANodeType::Geometry(geometry) = &node.node_type {
+                self.check_stack(&mut issues, node_index, HXAStack::Vertex, &geometry.vertex_stack);
+                self.check_stack(&mut issues, node_index, HXAStack::Corner, &geometry.corner_stack);
+                self.check_stack(&mut issues, node_index, HXAStack::Edge, &geometry.edge_stack);
+                self.check_stack(&mut issues, node_index, HXAStack::Face, &geometry.face_stack);
+            }
+        }
+
+        issues
+    }
+
+    fn check_stack(&self, issues: &mut Vec<HXASchemaIssue>, node_index: usize, stack: HXAStack, layers: &HXALayerStack) {
+        for convention in &self.conventions {
+            if convention.required && convention.stacks.first() == Some(&stack) && layers.find(&convention.name).is_none() {
+                issues.push(HXASchemaIssue::new(node_index, stack, &convention.name, convention, HXASchemaProblem::Missing));
+            }
+        }
+
+        for layer in &layers.layers {
+            let convention = match self.find(&layer.name) {
+                Some(convention) => convention,
+                None => continue,
+            };
+
+            if !convention.stacks.contains(&stack) {
+                issues.push(HXASchemaIssue::new(node_index, stack, &layer.name, convention, HXASchemaProblem::WrongStack));
+            }
+
+            let value_type = layer.layer_type.value_type();
+            if !value_type.is_some_and(|value_type| convention.types.contains(&value_type)) {
+                issues.push(HXASchemaIssue::new(node_index, stack, &layer.name, convention, HXASchemaProblem::WrongType { found: value_type }));
+            }
+
+            if convention.components.is_some_and(|components| components != layer.components) {
+                issues.push(HXASchemaIssue::new(node_index, stack, &layer.name, convention, HXASchemaProblem::WrongComponents { found: layer.components }));
+            }
+        }
+    }
+}
+
+impl Default for HXASchema {
+    fn default() -> Self {
+        Self::standard()
+    }
+}
+
+/// How a layer breaks its convention
+#[derive(Debug, Clone, PartialEq)]
+pub enum HXASchemaProblem {
+    /// A required layer is not in the stack
+    Missing,
+
+    /// The layer is in a stack the convention does not allow
+    WrongStack,
+
+    /// The layer is stored as a value type the convention does not allow
+    WrongType {
+        found: Option<HXALayerValueType>,
+    },
+
+    /// The layer has a different number of components than the convention
+    WrongComponents {
+        found: u8,
+    },
+}
+
+/// A layer that breaks a convention of a `HXASchema`
+#[derive(Debug, Clone)]
+pub struct HXASchemaIssue {
+    pub node_index: usize,
+    pub stack: HXAStack,
+    pub layer: String,
+    pub severity: HXASeverity,
+    pub problem: HXASchemaProblem,
+
+    /// The convention that was broken
+    pub convention: HXALayerConvention,
+}
+
+impl HXASchemaIssue {
+    fn new(node_index: usize, stack: HXAStack, layer: &str, convention: &HXALayerConvention, problem: HXASchemaProblem) -> Self {
+        HXASchemaIssue {
+            node_index,
+            stack,
+            layer: String::from(layer),
+            severity: convention.severity,
+            problem,
+            convention: convention.clone(),
+        }
+    }
+}
+
+impl fmt::Display for HXASchemaIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "node[{}].geometry.{}.layer[{:?}] ", self.node_index, self.stack.name(), self.layer)?;
+        match &self.problem {
+            HXASchemaProblem::Missing => write!(f, "is missing"),
+            HXASchemaProblem::WrongStack => write!(f, "belongs in {:?}", self.convention.stacks),
+            HXASchemaProblem::WrongType { found: Some(found) } => write!(f, "is {:?}, expected one of {:?}", found, self.convention.types),
+            HXASchemaProblem::WrongType { found: None } => write!(f, "has an unknown type, expected one of {:?}", self.convention.types),
+            HXASchemaProblem::WrongComponents { found } => write!(f, "has {} components, expected {}", found, self.convention.components.unwrap_or(*found)),
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use crate::enums::{HXAImageType, HXALayerValueType, HXANodeType};
+use crate::error::HXAError;
+use crate::hxfile::read_preamble;
+use crate::layer::HXALayerStack;
+use crate::macros::whereami;
+use crate::meta::HXAMeta;
+use crate::node::HXANode;
+use crate::reader::HXAReader;
+use std::io::Read;
+
+/// The structure of a HxA file without any layer data, see `HXAFile::peek`
+#[derive(Debug)]
+pub struct HXAFileSummary{
+    pub version: u8,
+    pub node_count: u32,
+    pub nodes: Vec<HXANodeSummary>,
+}
+
+impl HXAFileSummary {
+    /// Walks the nodes in the reader, skipping over layer payloads instead of reading them
+    pub fn read<R: Read>(input: &mut HXAReader<R>) -> Result<HXAFileSummary, HXAError> {
+        let (version, node_count) = read_preamble(input)?;
+
+        input.skip_layer_data = true;
+        let nodes = HXAFileSummary::read_nodes(input, node_count);
+        input.skip_layer_data = false;
+
+        Ok(HXAFileSummary{
+            version,
+            node_count,
+            nodes: nodes?,
+        })
+    }
+
+    fn read_nodes<R: Read>(input: &mut HXAReader<R>, node_count: u32) -> Result<Vec<HXANodeSummary>, HXAError> {
+        let mut nodes = Vec::with_capacity(1);
+
+        for node_index in 0..node_count{
+            let offset = whereami!(input);
+            input.skip_node = !input.options().filter.wants_node_index(node_index);
+            let mut node = HXANode::new();
+            node.parse(input).map_err(|err| err.within(format!("node[{}]", node_index)))?;
+
+            if !input.finish_unknown_node(&mut node, node_index, node_count, offset)? {
+                break;
+            }
+
+            if !input.skip_node {
+                nodes.push(HXANodeSummary::from_node(node, offset, whereami!(input) - offset));
+            }
+        }
+
+        Ok(nodes)
+    }
+
+    /// Number of geometry nodes in the file
+    pub fn geometry_count(&self) -> usize {
+        self.nodes.iter().filter(|node| matches!(node.node_type, HXANodeSummaryType::Geometry(_))).count()
+    }
+}
+
+#[derive(Debug)]
+pub enum HXANodeSummaryType {
+    MetaOnly,
+    Geometry(HXAGeometrySummary),
+    Image(HXAImageSummary),
+
+    /// A node type this crate does not know, only read when `LoadOptions::keep_unknown_nodes` is set
+    Unsupported {
+        type_code: u8
+    },
+}
+
+#[derive(Debug)]
+pub struct HXANodeSummary{
+    /// Byte offset of the node from the start of the file
+    pub offset: u64,
+
+    /// Size of the node in the file, in bytes
+    pub length: u64,
+
+    pub node_type: HXANodeSummaryType,
+
+    /// Meta data is small and is read in full
+    pub meta_data: Vec<HXAMeta>,
+}
+
+impl HXANodeSummary {
+    fn from_node(node: HXANode, offset: u64, length: u64) -> Self {
+        let node_type = match node.node_type {
+            HXANodeType::MetaOnly => HXANodeSummaryType::MetaOnly,
+            HXANodeType::Geometry(geometry) => HXANodeSummaryType::Geometry(HXAGeometrySummary{
+                vertex_count: geometry.vertex_count,
+                edge_corner_count: geometry.edge_corner_count,
+                face_count: geometry.face_count,
+                vertex_layers: HXALayerSummary::from_stack(&geometry.vertex_stack, geometry.vertex_count),
+                corner_layers: HXALayerSummary::from_stack(&geometry.corner_stack, geometry.edge_corner_count),
+                edge_layers: HXALayerSummary::from_stack(&geometry.edge_stack, geometry.edge_corner_count),
+                face_layers: HXALayerSummary::from_stack(&geometry.face_stack, geometry.face_count),
+            }),
+            HXANodeType::Image(image) => HXANodeSummaryType::Image(HXAImageSummary{
+                image_layers: HXALayerSummary::from_stack(&image.image_stack, image.pixel_count()),
+                image_type: image.image_type,
+                resolution: image.resolution,
+            }),
+            HXANodeType::Unsupported { type_code, .. } => HXANodeSummaryType::Unsupported { type_code },
+            HXANodeType::Unknown => unreachable!("Unknown node types are rejected when the type is read"),
+        };
+
+        HXANodeSummary{
+            offset,
+            length,
+            node_type,
+            meta_data: node.meta_data,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct HXAGeometrySummary{
+    pub vertex_count: u32,
+    pub edge_corner_count: u32,
+    pub face_count: u32,
+    pub vertex_layers: Vec<HXALayerSummary>,
+    pub corner_layers: Vec<HXALayerSummary>,
+    pub edge_layers: Vec<HXALayerSummary>,
+    pub face_layers: Vec<HXALayerSummary>,
+}
+
+#[derive(Debug)]
+pub struct HXAImageSummary{
+    pub image_type: HXAImageType,
+    pub resolution: [u32; 3],
+    pub image_layers: Vec<HXALayerSummary>,
+}
+
+#[derive(Debug)]
+pub struct HXALayerSummary{
+    pub name: String,
+    pub components: u8,
+
+    /// The type of the values, `None` if it is not a known type
+    pub value_type: Option<HXALayerValueType>,
+
+    /// Size of the layer data in the file, in bytes
+    pub data_length: u64,
+}
+
+impl HXALayerSummary {
+    fn from_stack(stack: &HXALayerStack, num_items: u32) -> Vec<HXALayerSummary> {
+        stack.layers.iter().map(|layer| {
+            let type_size = layer.layer_type.value_size().unwrap_or_default();
+
+            HXALayerSummary{
+                name: layer.name.clone(),
+                components: layer.components,
+                value_type: layer.layer_type.value_type(),
+                data_length: num_items as u64 * layer.components as u64 * type_size as u64,
+            }
+        }).collect()
+    }
+}
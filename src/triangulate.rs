@@ -0,0 +1,203 @@
+use crate::conventions::hard;
+use crate::enums::HXALayerDataType;
+use crate::error::HXAError;
+use crate::node::HXAGeometryNode;
+use crate::polygon::HXAPolygon;
+
+/// How `HXAGeometryNode::triangulate` splits polygons into triangles
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HXATriangulation {
+    /// Fans out from the first corner of each polygon, fast but only correct for convex polygons
+    Fan,
+
+    /// Clips ears off each polygon using the `vertex` positions, correct for concave polygons too
+    EarClipping,
+}
+
+/// Triangles made from the polygons of a geometry node
+///
+/// Triangles keep the winding of the polygon they come from
+#[derive(Debug, Clone, Default)]
+pub struct HXATriangles {
+    /// Three vertex indices per triangle, ready to be used as an index buffer
+    pub indices: Vec<u32>,
+
+    /// The polygon each triangle comes from, to look up values in the `face_stack`
+    pub triangle_faces: Vec<u32>,
+
+    /// The corner behind each entry of `indices`, to look up values in the `corner_stack`
+    pub triangle_corners: Vec<u32>,
+}
+
+impl HXATriangles {
+    /// Number of triangles
+    pub fn len(&self) -> usize {
+        self.triangle_faces.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.triangle_faces.is_empty()
+    }
+
+    fn push(&mut self, polygon: &HXAPolygon, face_corners: [usize; 3]) {
+        for corner in face_corners {
+            self.indices.push(polygon.vertex(corner));
+            self.triangle_corners.push((polygon.corners.start + corner) as u32);
+        }
+        self.triangle_faces.push(polygon.face);
+    }
+}
+
+impl HXAGeometryNode {
+    /// Splits every polygon into triangles, `None` if the layers needed are missing
+    ///
+    /// `Fan` needs the `reference` layer, `EarClipping` also needs a `FLOAT` or `DOUBLE` `vertex` layer with 3 components.
+    /// Polygons with fewer than 3 corners are left out
+    pub fn try_triangulate(&self, mode: HXATriangulation) -> Option<HXATriangles> {
+        let polygons = self.try_polygons()?;
+        let positions = match mode {
+            HXATriangulation::Fan => None,
+            HXATriangulation::EarClipping => Some(self.positions()?),
+        };
+
+        let mut triangles = HXATriangles::default();
+        for polygon in polygons {
+            if polygon.len() < 3 {
+                continue;
+            }
+
+            match &positions {
+                Some(positions) if polygon.len() > 3 => clip_ears(&mut triangles, &polygon, positions),
+                _ => {
+                    for corner in 1..polygon.len() - 1 {
+                        triangles.push(&polygon, [0, corner, corner + 1]);
+                    }
+                },
+            }
+        }
+
+        Some(triangles)
+    }
+
+    /// Splits every polygon into triangles
+    /// # Panics
+    /// Panics if the `reference` layer is missing, or the `vertex` layer when using `EarClipping`
+    /// # Example
+    /// ```rust,no_run
+    /// use hxa::triangulate::HXATriangulation;
+    ///
+    /// let my_hxa = hxa::HXAFile::from("Cube.hxa");
+    /// let geometry = my_hxa.get_first_geometry().expect("Expected to find a geometry node").0;
+    /// let triangles = geometry.triangulate(HXATriangulation::EarClipping);
+    /// let index_buffer: &[u32] = &triangles.indices;
+    /// ```
+    pub fn triangulate(&self, mode: HXATriangulation) -> HXATriangles {
+        self.try_triangulate(mode).expect("Expected a reference layer and, for ear clipping, a vertex layer")
+    }
+
+    /// The `vertex` layer as positions, `None` if it is not 3 `FLOAT` or `DOUBLE` components
+    pub(crate) fn positions(&self) -> Option<Vec<[f64; 3]>> {
+        let layer = self.vertex_stack.find(hard::BASE_VERTEX_LAYER_NAME)?;
+        if layer.components as u32 != hard::VERTEX_LAYER_COMPONENTS {
+            return None;
+        }
+
+        match &layer.layer_type {
+            HXALayerDataType::FLOAT(values) => Some(values.chunks_exact(3).map(|xyz| [xyz[0] as f64, xyz[1] as f64, xyz[2] as f64]).collect()),
+            HXALayerDataType::DOUBLE(values) => Some(values.chunks_exact(3).map(|xyz| [xyz[0], xyz[1], xyz[2]]).collect()),
+            _ => None,
+        }
+    }
+
+    /// The error to give when `try_polygons` or `positions` found no usable layer
+    pub(crate) fn missing_layer(&self) -> HXAError {
+        if self.try_polygons().is_none() {
+            HXAError::MissingLayer(String::from(hard::CORNER_LAYER_NAME))
+        } else {
+            HXAError::MissingLayer(String::from(hard::BASE_VERTEX_LAYER_NAME))
+        }
+    }
+}
+
+/// Triangulates a polygon by repeatedly cutting off a convex corner that has no other corner inside it
+fn clip_ears(triangles: &mut HXATriangles, polygon: &HXAPolygon, positions: &[[f64; 3]]) {
+    let points = match project(polygon, positions) {
+        Some(points) => points,
+        // Corners refer to missing vertices, there is nothing to measure
+        None => {
+            for corner in 1..polygon.len() - 1 {
+                triangles.push(polygon, [0, corner, corner + 1]);
+            }
+            return;
+        },
+    };
+
+    // Corners of the polygon that are not cut off yet
+    let mut remaining: Vec<usize> = (0..polygon.len()).collect();
+    while remaining.len() > 3 {
+        let count = remaining.len();
+        let is_ear = |index: usize| {
+            let (previous, current, next) = (remaining[(index + count - 1) % count], remaining[index], remaining[(index + 1) % count]);
+            let (a, b, c) = (points[previous], points[current], points[next]);
+            if cross(a, b, c) <= 0.0 {
+                return false;
+            }
+            remaining.iter()
+                .filter(|corner| ![previous, current, next].contains(corner))
+                .all(|corner| !inside_triangle(points[*corner], a, b, c))
+        };
+
+        // A degenerate polygon may have no ear left, cutting any corner still terminates
+        let ear = (0..count).find(|index| is_ear(*index)).unwrap_or(0);
+        triangles.push(polygon, [remaining[(ear + count - 1) % count], remaining[ear], remaining[(ear + 1) % count]]);
+        remaining.remove(ear);
+    }
+
+    triangles.push(polygon, [remaining[0], remaining[1], remaining[2]]);
+}
+
+/// Flattens the corners of a polygon onto the plane it mostly faces, so it winds counter clockwise
+fn project(polygon: &HXAPolygon, positions: &[[f64; 3]]) -> Option<Vec<[f64; 2]>> {
+    let corners = corner_positions(polygon, positions)?;
+    let normal = newell(&corners);
+
+    // Drop the axis the normal points along the most, and mirror if it points backwards
+    let axis = (0..3).max_by(|a, b| normal[*a].abs().total_cmp(&normal[*b].abs())).unwrap_or(2);
+    let (u, v) = match axis {
+        0 => (1, 2),
+        1 => (2, 0),
+        _ => (0, 1),
+    };
+    let flip = normal[axis] < 0.0;
+
+    Some(corners.iter().map(|corner| {
+        if flip { [corner[v], corner[u]] } else { [corner[u], corner[v]] }
+    }).collect())
+}
+
+/// The position of each corner of a polygon, `None` if it refers to missing vertices
+pub(crate) fn corner_positions(polygon: &HXAPolygon, positions: &[[f64; 3]]) -> Option<Vec<[f64; 3]>> {
+    polygon.vertices().map(|vertex| positions.get(vertex as usize).copied()).collect()
+}
+
+/// The normal of a polygon scaled by twice its area, Newell's method also handles polygons that are not quite flat
+pub(crate) fn newell(corners: &[[f64; 3]]) -> [f64; 3] {
+    let mut normal = [0.0f64; 3];
+    for (index, current) in corners.iter().enumerate() {
+        let next = corners[(index + 1) % corners.len()];
+        normal[0] += (current[1] - next[1]) * (current[2] + next[2]);
+        normal[1] += (current[2] - next[2]) * (current[0] + next[0]);
+        normal[2] += (current[0] - next[0]) * (current[1] + next[1]);
+    }
+    normal
+}
+
+/// Twice the signed area of the triangle, positive when it winds counter clockwise
+fn cross(a: [f64; 2], b: [f64; 2], c: [f64; 2]) -> f64 {
+    (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0])
+}
+
+/// Whether `point` is inside or on the edge of the counter clockwise triangle
+fn inside_triangle(point: [f64; 2], a: [f64; 2], b: [f64; 2], c: [f64; 2]) -> bool {
+    cross(a, b, point) >= 0.0 && cross(b, c, point) >= 0.0 && cross(c, a, point) >= 0.0
+}
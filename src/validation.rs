@@ -0,0 +1,213 @@
+use crate::conventions::hard;
+use crate::enums::HXALayerDataType;
+use crate::layer::HXALayerStack;
+use crate::node::HXAGeometryNode;
+use crate::polygon::decode_reference;
+use std::fmt;
+
+/// How serious a problem found by validation is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum HXASeverity {
+    /// The data is usable but probably not what the exporter meant
+    Warning,
+
+    /// The data breaks a hard convention, readers may fail or misread it
+    Error,
+}
+
+/// A problem found by `HXAGeometryNode::validate`
+#[derive(Debug, Clone, PartialEq)]
+pub enum HXAGeometryIssue {
+    /// The first vertex layer is not named `vertex`
+    MissingVertexLayer,
+
+    /// The `vertex` layer does not hold 3 `FLOAT` or `DOUBLE` components
+    BadVertexLayer {
+        components: u8,
+        type_code: Option<u8>,
+    },
+
+    /// The first corner layer is not named `reference`
+    MissingReferenceLayer,
+
+    /// The `reference` layer does not hold 1 `INT32` component
+    BadReferenceLayer {
+        components: u8,
+        type_code: Option<u8>,
+    },
+
+    /// A layer holds a different number of values than its stack count times its components
+    LayerLength {
+        /// Name of the stack, such as `corner_stack`
+        stack: &'static str,
+        layer: String,
+        expected: usize,
+        found: usize,
+    },
+
+    /// Two layers of the same stack share a name, `HXALayerStack::find` only finds the first
+    DuplicateLayerName {
+        stack: &'static str,
+        layer: String,
+    },
+
+    /// Corners refer to vertices at or past `vertex_count`, only the first one is given
+    ReferenceOutOfRange {
+        corner: u32,
+        vertex: u32,
+        count: usize,
+    },
+
+    /// The last corner does not end a polygon with a negative reference
+    OpenPolygon,
+
+    /// A polygon has fewer than 3 corners
+    ShortPolygon {
+        face: u32,
+        corners: u32,
+    },
+
+    /// The number of polygons in the `reference` layer differs from `face_count`
+    FaceCountMismatch {
+        polygons: u32,
+        face_count: u32,
+    },
+}
+
+impl HXAGeometryIssue {
+    pub fn severity(&self) -> HXASeverity {
+        match self {
+            HXAGeometryIssue::DuplicateLayerName { .. } | HXAGeometryIssue::ShortPolygon { .. } => HXASeverity::Warning,
+            _ => HXASeverity::Error,
+        }
+    }
+}
+
+impl fmt::Display for HXAGeometryIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HXAGeometryIssue::MissingVertexLayer => write!(f, "the first vertex layer is not {:?}", hard::BASE_VERTEX_LAYER_NAME),
+            HXAGeometryIssue::BadVertexLayer { components, type_code } => write!(
+                f, "the vertex layer has {} components of type {:?}, expected {} float or double components",
+                components, type_code, hard::VERTEX_LAYER_COMPONENTS
+            ),
+            HXAGeometryIssue::MissingReferenceLayer => write!(f, "the first corner layer is not {:?}", hard::CORNER_LAYER_NAME),
+            HXAGeometryIssue::BadReferenceLayer { components, type_code } => write!(
+                f, "the reference layer has {} components of type {:?}, expected {} int32 component",
+                components, type_code, hard::CORNER_LAYER_COMPONENTS
+            ),
+            HXAGeometryIssue::LayerLength { stack, layer, expected, found } => write!(f, "layer {:?} in {} holds {} values, expected {}", layer, stack, found, expected),
+            HXAGeometryIssue::DuplicateLayerName { stack, layer } => write!(f, "more than one layer in {} is named {:?}", stack, layer),
+            HXAGeometryIssue::ReferenceOutOfRange { corner, vertex, count } => write!(
+                f, "{} corner(s) refer to missing vertices, the first is corner {} referring to vertex {}",
+                count, corner, vertex
+            ),
+            HXAGeometryIssue::OpenPolygon => write!(f, "the last polygon is not closed with a negative reference"),
+            HXAGeometryIssue::ShortPolygon { face, corners } => write!(f, "polygon {} has only {} corners", face, corners),
+            HXAGeometryIssue::FaceCountMismatch { polygons, face_count } => write!(f, "the reference layer has {} polygons but face_count is {}", polygons, face_count),
+        }
+    }
+}
+
+impl HXAGeometryNode {
+    /// Checks that the node follows the hard conventions and that its layers agree with its counts
+    ///
+    /// An empty list means the node is coherent, see `HXAGeometryIssue::severity` for how serious each issue is
+    pub fn validate(&self) -> Vec<HXAGeometryIssue> {
+        let mut issues = Vec::new();
+
+        match self.vertex_stack.layers.get(hard::VERTEX_LAYER_ID as usize) {
+            Some(layer) if layer.name == hard::BASE_VERTEX_LAYER_NAME => {
+                let is_real = matches!(layer.layer_type, HXALayerDataType::FLOAT(_) | HXALayerDataType::DOUBLE(_));
+                if layer.components as u32 != hard::VERTEX_LAYER_COMPONENTS || !is_real {
+                    issues.push(HXAGeometryIssue::BadVertexLayer { components: layer.components, type_code: layer.layer_type.type_code() });
+                }
+            },
+            _ => issues.push(HXAGeometryIssue::MissingVertexLayer),
+        }
+
+        let references = match self.corner_stack.layers.get(hard::CORNER_LAYER_ID as usize) {
+            Some(layer) if layer.name == hard::CORNER_LAYER_NAME => {
+                if layer.components as u32 != hard::CORNER_LAYER_COMPONENTS || layer.try_as_vec_i32().is_none() {
+                    issues.push(HXAGeometryIssue::BadReferenceLayer { components: layer.components, type_code: layer.layer_type.type_code() });
+                    None
+                } else {
+                    layer.try_as_vec_i32()
+                }
+            },
+            _ => {
+                issues.push(HXAGeometryIssue::MissingReferenceLayer);
+                None
+            },
+        };
+
+        check_stack(&mut issues, "vertex_stack", &self.vertex_stack, self.vertex_count);
+        check_stack(&mut issues, "corner_stack", &self.corner_stack, self.edge_corner_count);
+        check_stack(&mut issues, "edge_stack", &self.edge_stack, self.edge_corner_count);
+        check_stack(&mut issues, "face_stack", &self.face_stack, self.face_count);
+
+        // The polygons can only be followed when the reference layer is the right length
+        if let Some(references) = references.filter(|references| references.len() == self.edge_corner_count as usize) {
+            self.check_references(&mut issues, references);
+        }
+
+        issues
+    }
+
+    /// Whether `validate` finds no issue of `HXASeverity::Error`
+    pub fn is_valid(&self) -> bool {
+        self.validate().iter().all(|issue| issue.severity() < HXASeverity::Error)
+    }
+
+    fn check_references(&self, issues: &mut Vec<HXAGeometryIssue>, references: &[i32]) {
+        let mut out_of_range: Option<HXAGeometryIssue> = None;
+        let mut polygons = 0u32;
+        let mut corners = 0u32;
+
+        for (corner, reference) in references.iter().enumerate() {
+            let vertex = decode_reference(*reference);
+            if vertex >= self.vertex_count {
+                match &mut out_of_range {
+                    Some(HXAGeometryIssue::ReferenceOutOfRange { count, .. }) => *count += 1,
+                    _ => out_of_range = Some(HXAGeometryIssue::ReferenceOutOfRange { corner: corner as u32, vertex, count: 1 }),
+                }
+            }
+
+            corners += 1;
+            if *reference < 0 {
+                if corners < 3 {
+                    issues.push(HXAGeometryIssue::ShortPolygon { face: polygons, corners });
+                }
+                polygons += 1;
+                corners = 0;
+            }
+        }
+
+        issues.extend(out_of_range);
+        if corners > 0 {
+            issues.push(HXAGeometryIssue::OpenPolygon);
+        }
+        if polygons != self.face_count {
+            issues.push(HXAGeometryIssue::FaceCountMismatch { polygons, face_count: self.face_count });
+        }
+    }
+}
+
+/// Checks the length of every layer in a stack and that their names are unique
+fn check_stack(issues: &mut Vec<HXAGeometryIssue>, stack_name: &'static str, stack: &HXALayerStack, num_items: u32) {
+    for (layer_index, layer) in stack.layers.iter().enumerate() {
+        let expected = num_items as usize * layer.components as usize;
+        if layer.layer_type.len() != expected {
+            issues.push(HXAGeometryIssue::LayerLength {
+                stack: stack_name,
+                layer: layer.name.clone(),
+                expected,
+                found: layer.layer_type.len(),
+            });
+        }
+
+        if stack.layers[..layer_index].iter().any(|other| other.name == layer.name) {
+            issues.push(HXAGeometryIssue::DuplicateLayerName { stack: stack_name, layer: layer.name.clone() });
+        }
+    }
+}
@@ -0,0 +1,458 @@
+use crate::enums::{HXAImageType, HXALayerDataType, HXALayerValueType};
+use crate::error::HXAError;
+use crate::hxfile::read_preamble;
+use crate::layer::HXALayer;
+use crate::macros::{buffer,read_bytes,whereami};
+use crate::meta::HXAMeta;
+use crate::options::LoadOptions;
+use crate::reader::HXAReader;
+use std::borrow::Cow;
+use std::io::Read;
+use std::str;
+
+/// Reinterprets little endian bytes as a slice of the desired type without copying
+///
+/// Falls back to copying when the host is not little endian or the bytes are not aligned for the type
+macro_rules!  borrow_or_copy{
+    ($bytes:ident $typ:tt) => {
+        {
+            // Safety: the numeric types stored in HxA have no padding and every bit pattern is valid
+            let (prefix, values, suffix) = unsafe { $bytes.align_to::<$typ>() };
+            if cfg!(target_endian = "little") && prefix.is_empty() && suffix.is_empty() {
+                Cow::Borrowed(values)
+            } else {
+                Cow::Owned(
+                    $bytes.chunks_exact(std::mem::size_of::<$typ>())
+                        .map(|chunk| <$typ>::from_le_bytes(chunk.try_into().expect("Expected chunks the size of the type")))
+                        .collect()
+                )
+            }
+        }
+    }
+}
+
+/// A HxA file parsed in place, where the layers borrow their data from the bytes of the file
+///
+/// Meta data is small and is copied into `HXAMeta` as usual, only the layer payloads are borrowed.
+/// Use this to inspect very large files without duplicating them in memory,
+/// together with `HXAMappedFile` when the `mmap` feature is enabled
+#[derive(Debug)]
+pub struct HXAFileView<'a>{
+    pub version: u8,
+    pub node_count: u32,
+    pub node_array: Vec<HXANodeView<'a>>,
+}
+
+impl<'a> HXAFileView<'a> {
+    /// Parses the structure of a HxA file, borrowing the layer data from `bytes`
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<HXAFileView<'a>, HXAError> {
+        HXAFileView::from_bytes_with(bytes, LoadOptions::default())
+    }
+
+    /// Parses the structure of a HxA file under the given limits, borrowing the layer data from `bytes`
+    ///
+    /// The `LoadFilter` of `options` is applied as when loading a `HXAFile`. A view cannot hold nodes of
+    /// unknown type, so they fail with `HXAError::UnknownNodeType` even when `keep_unknown_nodes` is set
+    pub fn from_bytes_with(bytes: &'a [u8], options: LoadOptions) -> Result<HXAFileView<'a>, HXAError> {
+        let mut input = HXAReader::with_options(bytes, options, Some(bytes.len() as u64));
+        let (version, node_count) = read_preamble(&mut input)?;
+
+        let mut node_array = Vec::with_capacity(1);
+        for node_index in 0..node_count{
+            input.skip_node = !input.options().filter.wants_node_index(node_index);
+            let mut new_node = HXANodeView::new();
+            new_node.parse(&mut input).map_err(|err| err.within(format!("node[{}]", node_index)))?;
+            if !input.skip_node {
+                node_array.push(new_node);
+            }
+        }
+
+        Ok(HXAFileView{
+            version,
+            // Nodes that were filtered out are not counted
+            node_count: node_array.len() as u32,
+            node_array,
+        })
+    }
+
+    /// Returns the first geometry node found, with its meta data
+    pub fn get_first_geometry(&self) -> Option<(&HXAGeometryView<'a>, &Vec<HXAMeta>)> {
+        self.node_array.iter().find_map(|node| match &node.node_type {
+            HXANodeViewType::Geometry(gnode) => Some((gnode, &node.meta_data)),
+            _ => None,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub enum HXANodeViewType<'a> {
+    MetaOnly,
+    Geometry(HXAGeometryView<'a>),
+    Image(HXAImageView<'a>),
+}
+
+#[derive(Debug)]
+pub struct HXANodeView<'a> {
+    pub node_type: HXANodeViewType<'a>,
+    pub meta_data: Vec<HXAMeta>,
+}
+
+impl<'a> HXANodeView<'a> {
+    fn new() -> Self {
+        HXANodeView{
+            node_type: HXANodeViewType::MetaOnly,
+            meta_data: Vec::with_capacity(1),
+        }
+    }
+
+    fn parse(self: &mut HXANodeView<'a>, input: &mut HXAReader<&'a [u8]>) -> Result<(), HXAError> {
+        let u8_node_type:u8 = read_bytes!(input u8);
+        self.node_type = match u8_node_type {
+            0 => HXANodeViewType::MetaOnly,
+            1 => HXANodeViewType::Geometry(HXAGeometryView::new()),
+            2 => HXANodeViewType::Image(HXAImageView::new()),
+            _ => return Err(HXAError::UnknownNodeType(u8_node_type).at(whereami!(input) - 1)),
+        };
+        if !input.options().filter.wants_node_type(u8_node_type) {
+            input.skip_node = true;
+        }
+
+        let metadata_count = read_bytes!(input u32);
+        for meta_index in 0..metadata_count{
+            let mut meta_data = HXAMeta::new();
+            meta_data.parse(input).map_err(|err| err.within(format!("meta[{}]", meta_index)))?;
+            self.meta_data.push(meta_data);
+        }
+
+        match &mut self.node_type {
+            HXANodeViewType::MetaOnly => (),
+            HXANodeViewType::Geometry(node) => {
+                node.parse(input).map_err(|err| err.within("geometry"))?;
+            },
+            HXANodeViewType::Image(node) => {
+                node.parse(input).map_err(|err| err.within("image"))?;
+            },
+        }
+
+        Ok(())
+    }
+}
+
+/// Borrowed counterpart of `HXAGeometryNode`
+#[derive(Debug)]
+pub struct HXAGeometryView<'a>{
+    pub vertex_count: u32,
+    pub vertex_stack: HXALayerStackView<'a>,
+    pub edge_corner_count: u32,
+    pub corner_stack: HXALayerStackView<'a>,
+    pub edge_stack: HXALayerStackView<'a>,
+    pub face_count: u32,
+    pub face_stack: HXALayerStackView<'a>,
+}
+
+impl<'a> HXAGeometryView<'a> {
+    fn new() -> Self {
+        HXAGeometryView{
+            vertex_count: 0,
+            vertex_stack: HXALayerStackView::new(),
+            edge_corner_count: 0,
+            corner_stack: HXALayerStackView::new(),
+            edge_stack: HXALayerStackView::new(),
+            face_count: 0,
+            face_stack: HXALayerStackView::new(),
+        }
+    }
+
+    fn parse(self: &mut HXAGeometryView<'a>, input: &mut HXAReader<&'a [u8]>) -> Result<(), HXAError> {
+        self.vertex_count = read_bytes!(input u32);
+        self.vertex_stack.parse(input, &self.vertex_count).map_err(|err| err.within("vertex_stack"))?;
+
+        self.edge_corner_count = read_bytes!(input u32);
+        self.corner_stack.parse(input, &self.edge_corner_count).map_err(|err| err.within("corner_stack"))?;
+        self.edge_stack.parse(input, &self.edge_corner_count).map_err(|err| err.within("edge_stack"))?;
+
+        self.face_count = read_bytes!(input u32);
+        self.face_stack.parse(input, &self.face_count).map_err(|err| err.within("face_stack"))?;
+
+        Ok(())
+    }
+}
+
+/// Borrowed counterpart of `HXAImageNode`
+#[derive(Debug)]
+pub struct HXAImageView<'a>{
+    pub image_type: HXAImageType,
+    pub resolution: [u32; 3],
+    pub image_stack: HXALayerStackView<'a>,
+}
+
+impl<'a> HXAImageView<'a> {
+    fn new() -> Self {
+        HXAImageView{
+            image_type: HXAImageType::Unknown,
+            resolution: [1, 1, 1],
+            image_stack: HXALayerStackView::new(),
+        }
+    }
+
+    fn parse(self: &mut HXAImageView<'a>, input: &mut HXAReader<&'a [u8]>) -> Result<(), HXAError> {
+        let u8_image_type:u8 = read_bytes!(input u8);
+        self.image_type = HXAImageType::from(u8_image_type);
+        if let HXAImageType::Unknown = self.image_type {
+            return Err(HXAError::UnknownImageType(u8_image_type).at(whereami!(input) - 1));
+        }
+
+        for dimension in 0..self.image_type.dimensions() {
+            self.resolution[dimension] = read_bytes!(input u32);
+        }
+
+        let pixel_count = self.image_type.pixel_count(&self.resolution);
+        self.image_stack.parse(input, &pixel_count).map_err(|err| err.within("image_stack"))?;
+
+        Ok(())
+    }
+}
+
+/// Borrowed counterpart of `HXALayerStack`
+#[derive(Debug)]
+pub struct HXALayerStackView<'a>{
+    pub layers: Vec<HXALayerView<'a>>,
+}
+
+impl<'a> HXALayerStackView<'a> {
+    fn new() -> Self {
+        HXALayerStackView{
+            layers: Vec::with_capacity(1),
+        }
+    }
+
+    fn parse(self: &mut HXALayerStackView<'a>, input: &mut HXAReader<&'a [u8]>, num_items: &u32) -> Result<(), HXAError> {
+        let layer_count = read_bytes!(input u32);
+        input.check_count("max_layers", layer_count, input.options().max_layers)?;
+
+        for layer_index in 0..layer_count{
+            let mut new_layer = HXALayerView::new();
+            let keep = new_layer.parse(input, num_items).map_err(|err| {
+                if new_layer.name.is_empty() {
+                    err.within(format!("layer[{}]", layer_index))
+                } else {
+                    err.within(format!("layer[{:?}]", new_layer.name))
+                }
+            })?;
+            if keep {
+                self.layers.push(new_layer);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Finds the first layer with the specified name
+    pub fn find(&self, layer_name:&str) -> Option<&HXALayerView<'a>>{
+        self.layers.iter().find(|layer| layer.name == layer_name)
+    }
+}
+
+/// Layer data that points into the file when it could be borrowed, or owns a copy when it could not
+#[derive(Debug)]
+pub enum HXALayerViewData<'a>{
+    UINT8 (&'a [u8]),
+    INT32 (Cow<'a, [i32]>),
+    FLOAT (Cow<'a, [f32]>),
+    DOUBLE (Cow<'a, [f64]>),
+}
+
+/// Borrowed counterpart of `HXALayer`
+#[derive(Debug)]
+pub struct HXALayerView<'a>{
+    pub name: &'a str,
+    pub components: u8,
+    pub layer_data: HXALayerViewData<'a>,
+}
+
+impl<'a> HXALayerView<'a> {
+    fn new() -> Self {
+        HXALayerView{
+            name: "",
+            components: 0,
+            layer_data: HXALayerViewData::UINT8(&[]),
+        }
+    }
+
+    /// Parses a layer, returns `false` if the `LoadFilter` left it out, in which case its data was not copied
+    fn parse(self: &mut HXALayerView<'a>, input: &mut HXAReader<&'a [u8]>, num_items: &u32) -> Result<bool, HXAError> {
+        let name_length:u8 = read_bytes!(input u8);
+        let name_buffer = input.borrow_bytes(name_length as usize)?;
+        self.name = match str::from_utf8(name_buffer) {
+            Ok(name) => name,
+            Err(err) => return Err(HXAError::from(err).at(whereami!(input) - name_buffer.len() as u64 + err.valid_up_to() as u64)),
+        };
+
+        self.components = read_bytes!(input u8);
+        let u8_layer_type:u8 = read_bytes!(input u8);
+
+        let value_type = match HXALayerValueType::from_type_code(u8_layer_type) {
+            Some(value_type) => value_type,
+            None => return Err(HXAError::UnknownLayerType(u8_layer_type).at(whereami!(input) - 1)),
+        };
+        let byte_count = (*num_items as usize)
+            .checked_mul(self.components as usize)
+            .and_then(|value_count| value_count.checked_mul(value_type.size()));
+        let bytes = match byte_count {
+            Some(byte_count) => input.borrow_bytes(byte_count)?,
+            None => return Err(HXAError::Truncated.at(whereami!(input))),
+        };
+
+        // The filter looks at a layer without its data, as it does when loading a `HXAFile`
+        let header = HXALayer {
+            name: String::from(self.name),
+            components: self.components,
+            layer_type: HXALayerDataType::from(u8_layer_type),
+        };
+        if input.skip_node || !input.options().filter.wants_layer(&header) {
+            return Ok(false);
+        }
+
+        self.layer_data = match value_type {
+            HXALayerValueType::UINT8 => HXALayerViewData::UINT8(bytes),
+            HXALayerValueType::INT32 => HXALayerViewData::INT32(borrow_or_copy!(bytes i32)),
+            HXALayerValueType::FLOAT => HXALayerViewData::FLOAT(borrow_or_copy!(bytes f32)),
+            HXALayerValueType::DOUBLE => HXALayerViewData::DOUBLE(borrow_or_copy!(bytes f64)),
+        };
+
+        Ok(true)
+    }
+
+    /// True when the data points straight into the file rather than into a copy
+    pub fn is_borrowed(&self) -> bool {
+        match &self.layer_data {
+            HXALayerViewData::UINT8(_) => true,
+            HXALayerViewData::INT32(data) => matches!(data, Cow::Borrowed(_)),
+            HXALayerViewData::FLOAT(data) => matches!(data, Cow::Borrowed(_)),
+            HXALayerViewData::DOUBLE(data) => matches!(data, Cow::Borrowed(_)),
+        }
+    }
+
+    /// The underlying slice for as long as the file bytes live, `None` if it is another type
+    pub fn try_borrowed_u8(&self) -> Option<&'a [u8]>{
+        match self.layer_data{
+            HXALayerViewData::UINT8(uint_array) => Some(uint_array),
+            _ => None,
+        }
+    }
+
+    /// The underlying slice for as long as the file bytes live, `None` if it is another type or had to be copied
+    pub fn try_borrowed_i32(&self) -> Option<&'a [i32]>{
+        match &self.layer_data{
+            HXALayerViewData::INT32(Cow::Borrowed(int_array)) => Some(int_array),
+            _ => None,
+        }
+    }
+
+    /// The underlying slice for as long as the file bytes live, `None` if it is another type or had to be copied
+    pub fn try_borrowed_f32(&self) -> Option<&'a [f32]>{
+        match &self.layer_data{
+            HXALayerViewData::FLOAT(Cow::Borrowed(float_array)) => Some(float_array),
+            _ => None,
+        }
+    }
+
+    /// The underlying slice for as long as the file bytes live, `None` if it is another type or had to be copied
+    pub fn try_borrowed_f64(&self) -> Option<&'a [f64]>{
+        match &self.layer_data{
+            HXALayerViewData::DOUBLE(Cow::Borrowed(double_array)) => Some(double_array),
+            _ => None,
+        }
+    }
+
+    pub fn try_as_slice_u8(&self) -> Option<&[u8]>{
+        match &self.layer_data{
+            HXALayerViewData::UINT8(uint_array) => Some(uint_array),
+            _ => None,
+        }
+    }
+
+    /// Gets and unwraps the underlying slice
+    /// # Panics
+    /// Panics if the underlying slice is the wrong type
+    pub fn as_slice_u8(&self) -> &[u8]{
+        self.try_as_slice_u8().expect("Expected the underlying type to be a [u8]")
+    }
+
+    pub fn try_as_slice_i32(&self) -> Option<&[i32]>{
+        match &self.layer_data{
+            HXALayerViewData::INT32(int_array) => Some(int_array),
+            _ => None,
+        }
+    }
+
+    /// Gets and unwraps the underlying slice
+    /// # Panics
+    /// Panics if the underlying slice is the wrong type
+    pub fn as_slice_i32(&self) -> &[i32]{
+        self.try_as_slice_i32().expect("Expected the underlying type to be a [i32]")
+    }
+
+    pub fn try_as_slice_f32(&self) -> Option<&[f32]>{
+        match &self.layer_data{
+            HXALayerViewData::FLOAT(float_array) => Some(float_array),
+            _ => None,
+        }
+    }
+
+    /// Gets and unwraps the underlying slice
+    /// # Panics
+    /// Panics if the underlying slice is the wrong type
+    pub fn as_slice_f32(&self) -> &[f32]{
+        self.try_as_slice_f32().expect("Expected the underlying type to be a [f32]")
+    }
+
+    pub fn try_as_slice_f64(&self) -> Option<&[f64]>{
+        match &self.layer_data{
+            HXALayerViewData::DOUBLE(double_array) => Some(double_array),
+            _ => None,
+        }
+    }
+
+    /// Gets and unwraps the underlying slice
+    /// # Panics
+    /// Panics if the underlying slice is the wrong type
+    pub fn as_slice_f64(&self) -> &[f64]{
+        self.try_as_slice_f64().expect("Expected the underlying type to be a [f64]")
+    }
+}
+
+/// A HxA file mapped into memory, which `HXAFileView`s can borrow from
+#[cfg(feature = "mmap")]
+#[derive(Debug)]
+pub struct HXAMappedFile{
+    map: memmap2::Mmap,
+}
+
+#[cfg(feature = "mmap")]
+impl HXAMappedFile {
+    /// Maps the file at `path` into memory without reading it
+    /// # Safety
+    /// The file must not be modified or truncated by this or any other process while it is mapped,
+    /// otherwise the views borrowing from it change underneath them or fault
+    pub unsafe fn open<P: AsRef<std::path::Path>>(path: P) -> Result<HXAMappedFile, HXAError> {
+        let file = std::fs::File::open(path)?;
+        let map = memmap2::Mmap::map(&file)?;
+        Ok(HXAMappedFile{ map })
+    }
+
+    /// Parses the structure of the mapped file, layers borrow from the mapping where alignment permits
+    pub fn view(&self) -> Result<HXAFileView<'_>, HXAError> {
+        HXAFileView::from_bytes(&self.map)
+    }
+
+    /// Parses the structure of the mapped file under the given limits and filters, see `HXAFileView::from_bytes_with`
+    pub fn view_with(&self, options: LoadOptions) -> Result<HXAFileView<'_>, HXAError> {
+        HXAFileView::from_bytes_with(&self.map, options)
+    }
+
+    /// The raw bytes of the file
+    pub fn bytes(&self) -> &[u8] {
+        &self.map
+    }
+}
@@ -0,0 +1,53 @@
+#![cfg(feature = "tokio")]
+
+use hxa::{HXAError, HXAFile, LoadOptions};
+use tokio::io::AsyncWriteExt;
+
+mod common;
+use common::{mixed_bytes, node_bytes};
+
+#[tokio::test]
+async fn async_load_and_save_round_trip() {
+    let bytes = mixed_bytes();
+    let full = HXAFile::from_bytes(&bytes).expect("Expected a valid file");
+
+    // A duplex stream hands the file over a few bytes at a time
+    let (mut sender, receiver) = tokio::io::duplex(7);
+    let sent = bytes.clone();
+    let sending = tokio::spawn(async move { sender.write_all(&sent).await });
+
+    let loaded = HXAFile::load_async(receiver).await.expect("Expected a valid file");
+    sending.await.expect("Expected the sender to finish").expect("Expected the bytes to be sent");
+    assert_eq!(loaded.version, full.version);
+    assert_eq!(loaded.node_count, 3);
+    for (node, complete) in loaded.node_array.iter().zip(&full.node_array) {
+        assert_eq!(node_bytes(node), node_bytes(complete));
+    }
+
+    let mut written = Vec::new();
+    loaded.save_async(&mut written).await.expect("Expected the file to be writable");
+    assert_eq!(written, bytes);
+}
+
+#[tokio::test]
+async fn async_load_reports_truncated_input() {
+    let bytes = mixed_bytes();
+
+    let err = HXAFile::load_async(&bytes[..200]).await.expect_err("Expected the file to be incomplete");
+    assert!(matches!(err.root(), HXAError::Truncated));
+    assert_eq!(err.offset(), Some(200));
+}
+
+#[tokio::test]
+async fn async_load_counts_payloads_against_the_limit() {
+    let bytes = mixed_bytes();
+
+    // The file is larger than the limit, but its payloads fit
+    let options = LoadOptions { max_total_bytes: 200, ..Default::default() };
+    assert!(bytes.len() > 200);
+    HXAFile::load_async_with(bytes.as_slice(), options).await.expect("Expected the payloads to fit");
+
+    let options = LoadOptions { max_total_bytes: 40, ..Default::default() };
+    let err = HXAFile::load_async_with(bytes.as_slice(), options).await.expect_err("Expected the limit to be exceeded");
+    assert!(matches!(err.root(), HXAError::LimitExceeded { limit: "max_total_bytes", .. }));
+}
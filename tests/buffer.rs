@@ -0,0 +1,61 @@
+use hxa::enums::HXALayerDataType;
+use hxa::schema::HXAStack;
+use hxa::{HXAError, HXAVertexBufferBuilder};
+
+mod common;
+use common::{cube, layer};
+
+#[test]
+fn cube_positions_weld_into_8_vertices() {
+    let geometry = cube();
+    let buffer = HXAVertexBufferBuilder::new().layer("vertex").build(&geometry).expect("Expected the layers to exist");
+
+    assert_eq!(buffer.stride, 3);
+    assert_eq!(buffer.vertex_count(), 8);
+    assert_eq!(buffer.indices.len(), 36);
+    assert_eq!(buffer.attributes[0].stack, HXAStack::Vertex);
+
+    // Every index still points at the position of the vertex it stands for
+    let triangles = geometry.triangulate(hxa::HXATriangulation::EarClipping);
+    for (index, vertex) in buffer.indices.iter().zip(&triangles.indices) {
+        let welded = &buffer.vertices[*index as usize * 3..*index as usize * 3 + 3];
+        assert_eq!(welded, &common::CUBE_POSITIONS[*vertex as usize * 3..*vertex as usize * 3 + 3]);
+    }
+}
+
+#[test]
+fn per_corner_normals_split_the_cube_into_24_vertices() {
+    let mut geometry = cube();
+    // The same normal on the 4 corners of each face, in the order of `CUBE_QUADS`
+    let face_normals = [[-1.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, -1.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, -1.0], [0.0, 0.0, 1.0]];
+    let normals: Vec<f32> = face_normals.iter().flat_map(|normal| std::iter::repeat_n(*normal, 4)).flatten().collect();
+    geometry.corner_stack.layers.push(layer("normal", 3, HXALayerDataType::FLOAT(normals)));
+
+    let buffer = HXAVertexBufferBuilder::new().layer("vertex").layer("normal").build(&geometry).expect("Expected the layers to exist");
+    assert_eq!(buffer.stride, 6);
+    assert_eq!(buffer.vertex_count(), 24);
+    assert_eq!(buffer.indices.len(), 36);
+    assert_eq!(buffer.attributes[1].offset, 3);
+    assert_eq!(buffer.attributes[1].stack, HXAStack::Corner);
+}
+
+#[test]
+fn missing_layer_is_an_error() {
+    let geometry = cube();
+    let err = HXAVertexBufferBuilder::new().layer("vertex").layer("uv").build(&geometry).expect_err("Expected the uv layer to be missing");
+    assert!(matches!(err, HXAError::MissingLayer(name) if name == "uv"));
+
+    let mut without_references = cube();
+    without_references.corner_stack = hxa::HXALayerStack::new();
+    let err = HXAVertexBufferBuilder::new().layer("vertex").build(&without_references).expect_err("Expected the reference layer to be missing");
+    assert!(matches!(err, HXAError::MissingLayer(name) if name == "reference"));
+}
+
+#[test]
+fn layer_of_the_wrong_length_is_an_error() {
+    let mut geometry = cube();
+    geometry.face_stack.layers.push(layer("material", 1, HXALayerDataType::INT32(vec![0; 5])));
+
+    let err = HXAVertexBufferBuilder::new().layer("material").build(&geometry).expect_err("Expected the layer to be rejected");
+    assert!(matches!(err, HXAError::InvalidLayer { layer, .. } if layer == "material"));
+}
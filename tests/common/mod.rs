@@ -0,0 +1,230 @@
+//! Hand encoded HxA files shared by the integration tests
+#![allow(dead_code)]
+
+pub fn push_name(bytes: &mut Vec<u8>, name: &str) {
+    bytes.push(name.len() as u8);
+    bytes.extend_from_slice(name.as_bytes());
+}
+
+pub fn push_u32(bytes: &mut Vec<u8>, value: u32) {
+    bytes.extend_from_slice(&value.to_le_bytes());
+}
+
+/// A single triangle with a uv layer, a text meta data and a nested meta data, encoded by hand
+pub fn triangle_bytes() -> Vec<u8> {
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(b"HxA\0");
+    bytes.push(3);
+    push_u32(&mut bytes, 1);
+
+    // Geometry node with 2 meta data entries
+    bytes.push(1);
+    push_u32(&mut bytes, 2);
+
+    push_name(&mut bytes, "author");
+    bytes.push(3);
+    push_u32(&mut bytes, 5);
+    bytes.extend_from_slice(b"eskil");
+
+    push_name(&mut bytes, "export");
+    bytes.push(5);
+    push_u32(&mut bytes, 1);
+    push_name(&mut bytes, "scale");
+    bytes.push(1);
+    push_u32(&mut bytes, 1);
+    bytes.extend_from_slice(&2.5f64.to_le_bytes());
+
+    // 3 vertices
+    push_u32(&mut bytes, 3);
+    push_u32(&mut bytes, 1);
+    push_name(&mut bytes, "vertex");
+    bytes.extend_from_slice(&[3, 2]);
+    for value in [0.0f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0] {
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    // 3 corners, no edge layers
+    push_u32(&mut bytes, 3);
+    push_u32(&mut bytes, 2);
+    push_name(&mut bytes, "reference");
+    bytes.extend_from_slice(&[1, 1]);
+    for value in [0i32, 1, -3] {
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+    push_name(&mut bytes, "uv");
+    bytes.extend_from_slice(&[2, 0]);
+    bytes.extend_from_slice(&[0, 0, 255, 0, 0, 255]);
+    push_u32(&mut bytes, 0);
+
+    // 1 face with a material
+    push_u32(&mut bytes, 1);
+    push_u32(&mut bytes, 1);
+    push_name(&mut bytes, "material");
+    bytes.extend_from_slice(&[1, 3]);
+    bytes.extend_from_slice(&7.0f64.to_le_bytes());
+
+    bytes
+}
+
+/// A 2x1 rgb image with binary and node meta data, followed by a 1x1 cube image with a float layer
+pub fn image_bytes() -> Vec<u8> {
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(b"HxA\0");
+    bytes.push(3);
+    push_u32(&mut bytes, 2);
+
+    bytes.push(2);
+    push_u32(&mut bytes, 2);
+    push_name(&mut bytes, "thumbnail");
+    bytes.push(4);
+    push_u32(&mut bytes, 3);
+    bytes.extend_from_slice(&[0xDE, 0xAD, 0x00]);
+    push_name(&mut bytes, "environment");
+    bytes.push(2);
+    push_u32(&mut bytes, 1);
+    push_u32(&mut bytes, 1);
+    bytes.push(2);
+    push_u32(&mut bytes, 2);
+    push_u32(&mut bytes, 1);
+    push_u32(&mut bytes, 1);
+    push_name(&mut bytes, "color");
+    bytes.extend_from_slice(&[3, 0]);
+    bytes.extend_from_slice(&[255, 0, 0, 0, 255, 0]);
+
+    bytes.push(2);
+    push_u32(&mut bytes, 0);
+    bytes.push(0);
+    push_u32(&mut bytes, 1);
+    push_u32(&mut bytes, 1);
+    push_u32(&mut bytes, 1);
+    push_name(&mut bytes, "light");
+    bytes.extend_from_slice(&[1, 2]);
+    for side in 0..6 {
+        bytes.extend_from_slice(&(side as f32).to_le_bytes());
+    }
+
+    bytes
+}
+
+/// The triangle followed by both images, so the file holds a geometry node and two image nodes
+pub fn mixed_bytes() -> Vec<u8> {
+    let mut bytes = triangle_bytes();
+    bytes[5..9].copy_from_slice(&3u32.to_le_bytes());
+    bytes.extend_from_slice(&image_bytes()[9..]);
+    bytes
+}
+
+/// The encoding of a single node, to compare nodes loaded in different ways
+pub fn node_bytes(node: &hxa::HXANode) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    node.write(&mut bytes).expect("Expected the node to be writable");
+    bytes
+}
+
+/// Positions of the corners of a 2x2x2 cube centered on the origin, vertex `x + 2y + 4z` sits on the positive side of each set bit
+pub const CUBE_POSITIONS: [f32; 24] = [
+    -1.0, -1.0, -1.0,
+    1.0, -1.0, -1.0,
+    -1.0, 1.0, -1.0,
+    1.0, 1.0, -1.0,
+    -1.0, -1.0, 1.0,
+    1.0, -1.0, 1.0,
+    -1.0, 1.0, 1.0,
+    1.0, 1.0, 1.0,
+];
+
+/// The six quads of the cube wound counter clockwise seen from outside, in the order -x, +x, -y, +y, -z, +z
+pub const CUBE_QUADS: [[i32; 4]; 6] = [
+    [0, 4, 6, 2],
+    [1, 3, 7, 5],
+    [0, 1, 5, 4],
+    [2, 6, 7, 3],
+    [0, 2, 3, 1],
+    [4, 5, 7, 6],
+];
+
+/// A cube made of 6 quads with only the `vertex` and `reference` layers, encoded by hand
+pub fn cube_bytes() -> Vec<u8> {
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(b"HxA\0");
+    bytes.push(3);
+    push_u32(&mut bytes, 1);
+
+    // Geometry node without meta data
+    bytes.push(1);
+    push_u32(&mut bytes, 0);
+
+    push_u32(&mut bytes, 8);
+    push_u32(&mut bytes, 1);
+    push_name(&mut bytes, "vertex");
+    bytes.extend_from_slice(&[3, 2]);
+    for value in CUBE_POSITIONS {
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    // 24 corners, no edge layers
+    push_u32(&mut bytes, 24);
+    push_u32(&mut bytes, 1);
+    push_name(&mut bytes, "reference");
+    bytes.extend_from_slice(&[1, 1]);
+    for quad in CUBE_QUADS {
+        for value in [quad[0], quad[1], quad[2], -quad[3] - 1] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+    push_u32(&mut bytes, 0);
+
+    push_u32(&mut bytes, 6);
+    push_u32(&mut bytes, 0);
+
+    bytes
+}
+
+/// Takes the first geometry node out of a file
+pub fn geometry(bytes: &[u8]) -> hxa::HXAGeometryNode {
+    let mut file = hxa::HXAFile::from_bytes(bytes).expect("Expected a valid file");
+    match file.node_array.remove(0).node_type {
+        hxa::enums::HXANodeType::Geometry(geometry) => geometry,
+        _ => panic!("Expected the first node to be a geometry node"),
+    }
+}
+
+/// The cube of `cube_bytes`, loaded
+pub fn cube() -> hxa::HXAGeometryNode {
+    geometry(&cube_bytes())
+}
+
+/// A layer holding `layer_type`, for tests that build or alter stacks
+pub fn layer(name: &str, components: u8, layer_type: hxa::enums::HXALayerDataType) -> hxa::HXALayer {
+    hxa::HXALayer {
+        name: String::from(name),
+        components,
+        layer_type,
+    }
+}
+
+/// The values of the `reference` layer, to alter the polygons of a geometry node
+pub fn references_mut(geometry: &mut hxa::HXAGeometryNode) -> &mut Vec<i32> {
+    match &mut geometry.corner_stack.layers[0].layer_type {
+        hxa::enums::HXALayerDataType::INT32(references) => references,
+        _ => panic!("Expected the first corner layer to be INT32"),
+    }
+}
+
+/// A geometry node with only the `vertex` and `reference` layers, built in memory
+pub fn polygon_mesh(positions: &[f32], references: &[i32]) -> hxa::HXAGeometryNode {
+    let mut geometry = hxa::HXAGeometryNode::new();
+    geometry.vertex_count = (positions.len() / 3) as u32;
+    geometry.vertex_stack.layers.push(layer("vertex", 3, hxa::enums::HXALayerDataType::FLOAT(positions.to_vec())));
+    geometry.edge_corner_count = references.len() as u32;
+    geometry.corner_stack.layers.push(layer("reference", 1, hxa::enums::HXALayerDataType::INT32(references.to_vec())));
+    geometry.face_count = references.iter().filter(|reference| **reference < 0).count() as u32;
+    geometry
+}
+
+/// Writes `bytes` to a file of its own in the temporary directory, for the entry points that take a path
+pub fn temp_file(name: &str, bytes: &[u8]) -> std::path::PathBuf {
+    let path = std::env::temp_dir().join(format!("hxa-{}-{}.hxa", name, std::process::id()));
+    std::fs::write(&path, bytes).expect("Expected to write the file");
+    path
+}
@@ -0,0 +1,145 @@
+use hxa::{HXAError, HXAFile};
+use std::io::Read;
+
+mod common;
+
+/// Loads `bytes` both from a slice, whose length is known, and from a reader, whose length is not
+fn load_both_ways(bytes: &[u8]) -> [HXAError; 2] {
+    [
+        HXAFile::from_bytes(bytes).expect_err("Expected the slice to fail to load"),
+        HXAFile::from_reader(bytes).expect_err("Expected the reader to fail to load"),
+    ]
+}
+
+/// A reader handing out a single byte per call
+struct Trickle<'a>(&'a [u8]);
+
+impl Read for Trickle<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.0.is_empty() || buf.is_empty() {
+            return Ok(0);
+        }
+        buf[0] = self.0[0];
+        self.0 = &self.0[1..];
+        Ok(1)
+    }
+}
+
+#[test]
+fn valid_file_opens() {
+    let path = common::temp_file("valid", &common::triangle_bytes());
+    let loaded = HXAFile::open(&path);
+    std::fs::remove_file(&path).expect("Expected to remove the file");
+
+    let loaded = loaded.expect("Expected a valid file");
+    let geometry = loaded.get_first_geometry().expect("Expected a geometry node").0;
+    assert_eq!(geometry.vertex_count, 3);
+    assert_eq!(geometry.face_count, 1);
+}
+
+#[test]
+fn reader_handing_out_single_bytes_loads_the_same_file() {
+    let bytes = common::triangle_bytes();
+    let from_bytes = HXAFile::from_bytes(&bytes).expect("Expected a valid file");
+    let from_reader = HXAFile::from_reader(Trickle(&bytes)).expect("Expected a valid file");
+
+    let geometry = from_bytes.get_first_geometry().expect("Expected a geometry node").0;
+    let trickled = from_reader.get_first_geometry().expect("Expected a geometry node").0;
+    let vertices = geometry.vertex_stack.find("vertex").expect("Expected a vertex layer").as_vec_f32();
+    assert_eq!(trickled.vertex_stack.find("vertex").expect("Expected a vertex layer").as_vec_f32(), vertices);
+    assert_eq!(vertices, &vec![0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0]);
+
+    let err = HXAFile::from_reader(Trickle(&bytes[..85])).expect_err("Expected the file to be incomplete");
+    assert_eq!(err.offset(), Some(85));
+}
+
+#[test]
+fn missing_file_is_an_io_error() {
+    let path = std::env::temp_dir().join("hxa-does-not-exist.hxa");
+    let err = HXAFile::open(path).expect_err("Expected the file to be missing");
+    assert!(matches!(err, HXAError::Io(_)));
+}
+
+#[test]
+fn bad_magic_is_reported_at_the_start() {
+    let mut bytes = common::triangle_bytes();
+    bytes[..4].copy_from_slice(b"HxB\0");
+
+    for err in load_both_ways(&bytes) {
+        assert!(matches!(err.root(), HXAError::BadMagic(0x00427848)));
+        assert_eq!(err.offset(), Some(0));
+        assert_eq!(err.path(), Some(""));
+    }
+}
+
+#[test]
+fn unsupported_version_is_reported_at_the_version_byte() {
+    let mut bytes = common::triangle_bytes();
+    bytes[4] = 99;
+
+    for err in load_both_ways(&bytes) {
+        assert!(matches!(err.root(), HXAError::UnsupportedVersion(99)));
+        assert_eq!(err.offset(), Some(4));
+        assert_eq!(err.path(), Some(""));
+    }
+}
+
+#[test]
+fn file_cut_anywhere_is_truncated() {
+    let bytes = common::triangle_bytes();
+
+    for length in 0..bytes.len() {
+        for err in load_both_ways(&bytes[..length]) {
+            assert!(matches!(err.root(), HXAError::Truncated), "Expected a file cut after {} bytes to be truncated, got {}", length, err);
+        }
+    }
+}
+
+#[test]
+fn file_cut_in_the_header_is_truncated() {
+    let bytes = common::triangle_bytes();
+
+    for err in load_both_ways(&bytes[..7]) {
+        assert!(matches!(err.root(), HXAError::Truncated));
+        assert_eq!(err.offset(), Some(7));
+        assert_eq!(err.path(), Some(""));
+    }
+}
+
+#[test]
+fn file_cut_in_a_meta_data_is_truncated_with_its_path() {
+    let bytes = common::triangle_bytes();
+
+    // Inside the text of the "author" meta data, which starts at byte 26
+    for err in load_both_ways(&bytes[..28]) {
+        assert!(matches!(err.root(), HXAError::Truncated));
+        assert_eq!(err.offset(), Some(28));
+        assert_eq!(err.path(), Some("node[0].meta[0]"));
+    }
+}
+
+#[test]
+fn file_cut_in_a_layer_is_truncated_with_its_path() {
+    let bytes = common::triangle_bytes();
+
+    // Inside the values of the "vertex" layer, which start at byte 79
+    for err in load_both_ways(&bytes[..85]) {
+        assert!(matches!(err.root(), HXAError::Truncated));
+        assert_eq!(err.offset(), Some(85));
+        assert_eq!(err.path(), Some("node[0].geometry.vertex_stack.layer[\"vertex\"]"));
+        assert!(err.to_string().contains("node[0].geometry.vertex_stack.layer[\"vertex\"]"));
+    }
+}
+
+#[test]
+fn unknown_layer_type_is_reported_at_the_type_byte() {
+    let mut bytes = common::triangle_bytes();
+    // The type of the "vertex" layer is the byte before its values
+    bytes[78] = 9;
+
+    for err in load_both_ways(&bytes) {
+        assert!(matches!(err.root(), HXAError::UnknownLayerType(9)));
+        assert_eq!(err.offset(), Some(78));
+        assert_eq!(err.path(), Some("node[0].geometry.vertex_stack.layer[\"vertex\"]"));
+    }
+}
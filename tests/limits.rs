@@ -0,0 +1,146 @@
+use hxa::{HXAError, HXAFile, LoadOptions};
+
+mod common;
+use common::{push_name, push_u32};
+
+/// A geometry node claiming 0xFFFFFFFF vertices with 255 `DOUBLE` components each, and no data behind it
+fn huge_layer_bytes() -> Vec<u8> {
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(b"HxA\0");
+    bytes.push(3);
+    push_u32(&mut bytes, 1);
+
+    bytes.push(1);
+    push_u32(&mut bytes, 0);
+    push_u32(&mut bytes, u32::MAX);
+    push_u32(&mut bytes, 1);
+    push_name(&mut bytes, "v");
+    bytes.extend_from_slice(&[255, 3]);
+
+    bytes
+}
+
+/// A meta node with a `TEXT` meta data claiming 0xFFFFFFFF bytes, and no data behind it
+fn huge_text_bytes() -> Vec<u8> {
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(b"HxA\0");
+    bytes.push(3);
+    push_u32(&mut bytes, 1);
+
+    bytes.push(0);
+    push_u32(&mut bytes, 1);
+    push_name(&mut bytes, "notes");
+    bytes.push(3);
+    push_u32(&mut bytes, u32::MAX);
+    bytes.extend_from_slice(b"short");
+
+    bytes
+}
+
+/// A meta node holding `depth` `META` meta data, each nested in the one before
+fn nested_meta_bytes(depth: u32) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(b"HxA\0");
+    bytes.push(3);
+    push_u32(&mut bytes, 1);
+
+    bytes.push(0);
+    push_u32(&mut bytes, 1);
+    for level in 0..depth {
+        push_name(&mut bytes, "n");
+        bytes.push(5);
+        push_u32(&mut bytes, if level + 1 < depth { 1 } else { 0 });
+    }
+
+    bytes
+}
+
+/// Loads `bytes` both from a slice and from a reader under `options`, expecting both to exceed `limit`
+fn assert_limit_exceeded(bytes: &[u8], options: LoadOptions, limit: &str, offset: u64) {
+    let errors = [
+        HXAFile::from_bytes_with(bytes, options.clone()).expect_err("Expected the slice to exceed the limit"),
+        HXAFile::from_reader_with(bytes, options).expect_err("Expected the reader to exceed the limit"),
+    ];
+    for err in errors {
+        match err.root() {
+            HXAError::LimitExceeded { limit: exceeded, .. } => assert_eq!(*exceeded, limit),
+            root => panic!("Expected {} to be exceeded, got {}", limit, root),
+        }
+        assert_eq!(err.offset(), Some(offset));
+    }
+}
+
+#[test]
+fn nesting_deeper_than_the_limit_is_rejected() {
+    let bytes = nested_meta_bytes(200);
+    assert!(HXAFile::from_bytes(&nested_meta_bytes(64)).is_ok());
+
+    // Each level is a 2 byte name, a type and a count, the 65th level is entered once its count is read
+    let options = LoadOptions::default();
+    assert_eq!(options.max_nesting_depth, 64);
+    assert_limit_exceeded(&bytes, options, "max_nesting_depth", 14 + 7 * 65);
+}
+
+#[test]
+fn node_count_over_the_limit_is_rejected_at_the_count() {
+    let options = LoadOptions {
+        max_nodes: 2,
+        ..Default::default()
+    };
+    assert!(HXAFile::from_bytes_with(&common::triangle_bytes(), options.clone()).is_ok());
+    assert_limit_exceeded(&common::mixed_bytes(), options, "max_nodes", 5);
+}
+
+#[test]
+fn layer_count_over_the_limit_is_rejected_at_the_count() {
+    let options = LoadOptions {
+        max_layers: 1,
+        ..Default::default()
+    };
+    // The vertex stack holds 1 layer, the count of the 2 corner layers follows the corner count at byte 115
+    assert_limit_exceeded(&common::triangle_bytes(), options, "max_layers", 119);
+}
+
+#[test]
+fn payloads_over_the_limit_are_rejected_where_they_start() {
+    let options = LoadOptions {
+        max_total_bytes: 40,
+        ..Default::default()
+    };
+    // The text and the double of the meta data fit, the 36 bytes of vertices at byte 79 do not
+    assert_limit_exceeded(&common::triangle_bytes(), options, "max_total_bytes", 79);
+}
+
+#[test]
+fn reader_of_unknown_length_does_not_trust_layer_sizes() {
+    let bytes = huge_layer_bytes();
+    assert_eq!(bytes.len(), 26);
+
+    let err = HXAFile::from_reader(bytes.as_slice()).expect_err("Expected the layer to be cut short");
+    assert!(matches!(err.root(), HXAError::Truncated));
+    assert_eq!(err.offset(), Some(26));
+    assert_eq!(err.path(), Some("node[0].geometry.vertex_stack.layer[\"v\"]"));
+
+    let err = HXAFile::from_bytes(&bytes).expect_err("Expected the layer to be cut short");
+    assert!(matches!(err.root(), HXAError::Truncated));
+}
+
+#[test]
+fn reader_of_unknown_length_does_not_trust_text_sizes() {
+    let bytes = huge_text_bytes();
+
+    let err = HXAFile::from_reader(bytes.as_slice()).expect_err("Expected the text to be cut short");
+    assert!(matches!(err.root(), HXAError::Truncated));
+    assert_eq!(err.offset(), Some(bytes.len() as u64));
+    assert_eq!(err.path(), Some("node[0].meta[0]"));
+}
+
+#[test]
+fn reader_of_unknown_length_reads_complete_files() {
+    let bytes = common::triangle_bytes();
+
+    let from_reader = HXAFile::from_reader(bytes.as_slice()).expect("Expected a valid file");
+    let mut written = Vec::new();
+    from_reader.write_to(&mut written).expect("Expected the file to be writable");
+    assert_eq!(bytes, written);
+}
@@ -0,0 +1,282 @@
+use hxa::enums::{HXALayerValueType, HXANodeType};
+use hxa::{HXAError, HXAFile, HXAIndexedFile, HXALayer, HXANodeSummaryType, HXAParser, HXAProgress, HXAReader, LoadFilter, LoadOptions};
+use std::io::Cursor;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+mod common;
+use common::{mixed_bytes, node_bytes};
+
+#[test]
+fn recover_keeps_the_nodes_before_the_cut() {
+    let bytes = mixed_bytes();
+    let cut = &bytes[..bytes.len() - 10];
+
+    let recovery = HXAFile::recover_bytes(cut).expect("Expected the header to be readable");
+    assert!(!recovery.is_complete());
+    assert_eq!(recovery.expected_nodes, 3);
+    assert_eq!(recovery.file.node_count, 2);
+    assert_eq!(recovery.file.node_array.len(), 2);
+
+    let stopped = recovery.stopped.expect("Expected the last node to be cut short");
+    assert!(matches!(stopped.root(), HXAError::Truncated));
+    assert_eq!(stopped.offset(), Some(cut.len() as u64));
+    assert_eq!(stopped.path(), Some("node[2].image.image_stack.layer[\"light\"]"));
+
+    // The nodes that were kept are the same as in the complete file
+    let full = HXAFile::from_bytes(&bytes).expect("Expected a valid file");
+    for (recovered, complete) in recovery.file.node_array.iter().zip(&full.node_array) {
+        assert_eq!(node_bytes(recovered), node_bytes(complete));
+    }
+
+    let recovery = HXAFile::recover_bytes(&bytes).expect("Expected a valid file");
+    assert!(recovery.is_complete());
+    assert_eq!(recovery.file.node_count, 3);
+}
+
+#[test]
+fn peek_finds_the_node_boundaries() {
+    let bytes = mixed_bytes();
+    let full = HXAFile::from_bytes(&bytes).expect("Expected a valid file");
+    let summary = HXAFile::peek_bytes(&bytes).expect("Expected a valid file");
+
+    assert_eq!(summary.version, 3);
+    assert_eq!(summary.node_count, 3);
+    assert_eq!(summary.geometry_count(), 1);
+
+    // Nodes follow the 9 byte header back to back, each as long as its encoding
+    let mut offset = 9;
+    for (node, complete) in summary.nodes.iter().zip(&full.node_array) {
+        assert_eq!(node.offset, offset);
+        assert_eq!(node.length, node_bytes(complete).len() as u64);
+        offset += node.length;
+    }
+    assert_eq!(offset, bytes.len() as u64);
+
+    match &summary.nodes[0].node_type {
+        HXANodeSummaryType::Geometry(geometry) => {
+            assert_eq!(geometry.vertex_count, 3);
+            let vertex = &geometry.vertex_layers[0];
+            assert_eq!(vertex.name, "vertex");
+            assert_eq!(vertex.value_type, Some(HXALayerValueType::FLOAT));
+            assert_eq!(vertex.data_length, 3 * 3 * 4);
+            let material = &geometry.face_layers[0];
+            assert_eq!(material.value_type, Some(HXALayerValueType::DOUBLE));
+            assert_eq!(material.data_length, 8);
+        },
+        _ => panic!("Expected a geometry node"),
+    }
+    assert_eq!(summary.nodes[0].meta_data.len(), 2);
+}
+
+fn load_filtered(filter: LoadFilter) -> HXAFile {
+    let options = LoadOptions { filter, ..Default::default() };
+    HXAFile::from_bytes_with(&mixed_bytes(), options).expect("Expected a valid file")
+}
+
+#[test]
+fn filter_by_layer_name() {
+    let file = load_filtered(LoadFilter {
+        layer_names: Some(vec![String::from("vertex"), String::from("reference")]),
+        ..Default::default()
+    });
+
+    let geometry = file.get_first_geometry().expect("Expected the geometry node").0;
+    assert_eq!(geometry.vertex_stack.layer_count, 1);
+    assert_eq!(geometry.corner_stack.layer_count, 1);
+    assert!(geometry.corner_stack.find("uv").is_none());
+    assert!(geometry.face_stack.layers.is_empty());
+    assert_eq!(geometry.corner_stack.find("reference").map(|layer| layer.as_vec_i32().clone()), Some(vec![0, 1, -3]));
+
+    // Image layers are left out as well, the nodes themselves are kept
+    assert_eq!(file.node_count, 3);
+    match &file.node_array[1].node_type {
+        HXANodeType::Image(image) => assert!(image.image_stack.layers.is_empty()),
+        _ => panic!("Expected an image node"),
+    }
+}
+
+#[test]
+fn filter_by_layer_predicate() {
+    let file = load_filtered(LoadFilter {
+        layer_predicate: Some(Arc::new(|layer: &HXALayer| layer.components == 1)),
+        ..Default::default()
+    });
+
+    let geometry = file.get_first_geometry().expect("Expected the geometry node").0;
+    assert!(geometry.vertex_stack.layers.is_empty());
+    assert!(geometry.corner_stack.find("reference").is_some());
+    assert!(geometry.corner_stack.find("uv").is_none());
+    assert!(geometry.face_stack.find("material").is_some());
+}
+
+#[test]
+fn filter_by_node_index() {
+    let full = HXAFile::from_bytes(&mixed_bytes()).expect("Expected a valid file");
+    let file = load_filtered(LoadFilter {
+        node_indices: Some(vec![0, 2]),
+        ..Default::default()
+    });
+
+    assert_eq!(file.node_count, 2);
+    assert_eq!(node_bytes(&file.node_array[0]), node_bytes(&full.node_array[0]));
+    assert_eq!(node_bytes(&file.node_array[1]), node_bytes(&full.node_array[2]));
+}
+
+#[test]
+fn filter_by_node_type() {
+    let file = load_filtered(LoadFilter {
+        node_types: Some(vec![2]),
+        ..Default::default()
+    });
+
+    assert_eq!(file.node_count, 2);
+    assert!(file.node_array.iter().all(|node| matches!(node.node_type, HXANodeType::Image(_))));
+    assert!(file.get_first_geometry().is_none());
+}
+
+#[test]
+fn indexed_file_loads_single_nodes() {
+    let bytes = mixed_bytes();
+    let full = HXAFile::from_bytes(&bytes).expect("Expected a valid file");
+
+    let mut indexed = HXAIndexedFile::new(Cursor::new(bytes), LoadOptions::default()).expect("Expected the file to be indexed");
+    assert_eq!(indexed.node_count(), 3);
+
+    let node = indexed.load_node(1).expect("Expected the node to load");
+    assert_eq!(node_bytes(&node), node_bytes(&full.node_array[1]));
+
+    // Going back to an earlier node works as well
+    let node = indexed.load_node(0).expect("Expected the node to load");
+    assert_eq!(node_bytes(&node), node_bytes(&full.node_array[0]));
+}
+
+#[test]
+fn indexed_file_rejects_missing_nodes() {
+    let mut indexed = HXAIndexedFile::new(Cursor::new(mixed_bytes()), LoadOptions::default()).expect("Expected the file to be indexed");
+
+    let err = indexed.load_node(3).expect_err("Expected there to be no fourth node");
+    assert!(matches!(err, HXAError::NoSuchNode { node_index: 3, node_count: 3 }));
+}
+
+#[test]
+fn parser_fed_one_byte_at_a_time_matches_a_full_load() {
+    let bytes = mixed_bytes();
+    let full = HXAFile::from_bytes(&bytes).expect("Expected a valid file");
+
+    let mut parser = HXAParser::new();
+    let mut nodes = Vec::new();
+    for byte in &bytes {
+        nodes.extend(parser.feed(std::slice::from_ref(byte)).expect("Expected a valid file"));
+    }
+    assert!(parser.is_complete());
+    assert_eq!(parser.node_count(), Some(3));
+    assert_eq!(parser.bytes_fed(), bytes.len() as u64);
+    assert!(parser.finish().expect("Expected the file to be complete").is_none());
+
+    assert_eq!(nodes.len(), full.node_array.len());
+    for (node, complete) in nodes.iter().zip(&full.node_array) {
+        assert_eq!(node_bytes(node), node_bytes(complete));
+    }
+}
+
+/// A meta only node with `count` `INT64` meta data entries, each holding a single value
+fn many_metas_bytes(count: u32) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(b"HxA\0");
+    bytes.push(3);
+    common::push_u32(&mut bytes, 1);
+
+    bytes.push(0);
+    common::push_u32(&mut bytes, count);
+    for value in 0..count as i64 {
+        common::push_name(&mut bytes, "m");
+        bytes.push(0);
+        common::push_u32(&mut bytes, 1);
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    bytes
+}
+
+#[test]
+fn parser_does_not_walk_a_node_again_for_every_chunk() {
+    let bytes = many_metas_bytes(20_000);
+
+    let start = Instant::now();
+    let full = HXAFile::from_bytes(&bytes).expect("Expected a valid file");
+    let full_load = start.elapsed();
+
+    let start = Instant::now();
+    let mut parser = HXAParser::new();
+    let mut nodes = Vec::new();
+    for chunk in bytes.chunks(1024) {
+        nodes.extend(parser.feed(chunk).expect("Expected a valid file"));
+    }
+    let chunked_load = start.elapsed();
+
+    assert_eq!(nodes.len(), 1);
+    assert_eq!(node_bytes(&nodes[0]), node_bytes(&full.node_array[0]));
+
+    // Walking the whole node for each of the 290 chunks would take about 150 times as long as a full load
+    assert!(
+        chunked_load < full_load * 20 + Duration::from_millis(100),
+        "Expected the chunked load to take about as long as a full load, it took {:?} against {:?}", chunked_load, full_load
+    );
+}
+
+#[test]
+fn parser_reports_missing_nodes_on_finish() {
+    let bytes = mixed_bytes();
+
+    let mut parser = HXAParser::new();
+    let nodes = parser.feed(&bytes[..200]).expect("Expected a valid start of a file");
+    assert_eq!(nodes.len(), 1);
+    assert_eq!(parser.nodes_parsed(), 1);
+
+    let err = parser.finish().expect_err("Expected the file to be incomplete");
+    assert!(matches!(err.root(), HXAError::Truncated));
+    assert_eq!(err.offset(), Some(200));
+}
+
+#[test]
+fn observer_can_cancel_a_load() {
+    let bytes = mixed_bytes();
+
+    // Stop once the first node is done
+    let mut input = HXAReader::with_options(bytes.as_slice(), LoadOptions::default(), Some(bytes.len() as u64));
+    input.set_observer(|progress: &HXAProgress| progress.nodes_done < 1);
+    let err = HXAFile::from_hxa_reader(&mut input).expect_err("Expected the load to be cancelled");
+    assert!(matches!(err.root(), HXAError::Cancelled));
+    assert_eq!(err.offset(), Some(9 + 180));
+
+    // An observer that never cancels sees the load move forward up to every node
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let observed = seen.clone();
+    let mut input = HXAReader::with_options(bytes.as_slice(), LoadOptions::default(), Some(bytes.len() as u64));
+    input.set_observer(move |progress: &HXAProgress| {
+        observed.lock().unwrap().push((progress.bytes, progress.nodes_done));
+        true
+    });
+    HXAFile::from_hxa_reader(&mut input).expect("Expected a valid file");
+
+    let seen = seen.lock().unwrap();
+    assert!(seen.windows(2).all(|pair| pair[0].0 <= pair[1].0 && pair[0].1 <= pair[1].1));
+    assert_eq!(seen.last(), Some(&(bytes.len() as u64, 3)));
+}
+
+#[test]
+fn observer_can_cancel_a_save() {
+    let file = HXAFile::from_bytes(&mixed_bytes()).expect("Expected a valid file");
+
+    let mut written = Vec::new();
+    let err = file.write_to_observed(&mut written, |progress: &HXAProgress| progress.nodes_done < 2)
+        .expect_err("Expected the save to be cancelled");
+    assert!(matches!(err.root(), HXAError::Cancelled));
+    assert_eq!(err.path(), Some("node[1]"));
+    assert_eq!(written.len(), 9 + 180 + 71);
+
+    let mut written = Vec::new();
+    file.write_to_observed(&mut written, |_: &HXAProgress| true).expect("Expected the file to be writable");
+    assert_eq!(written, mixed_bytes());
+}
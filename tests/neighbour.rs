@@ -0,0 +1,72 @@
+use hxa::neighbour::{compute_neighbours, NO_NEIGHBOUR};
+use hxa::HXAError;
+
+mod common;
+use common::{cube, polygon_mesh};
+
+/// The vertices each corner's edge runs from and to
+fn edges(geometry: &hxa::HXAGeometryNode) -> Vec<(u32, u32)> {
+    geometry.polygons().flat_map(|polygon| {
+        (0..polygon.len()).map(move |corner| (polygon.vertex(corner), polygon.vertex((corner + 1) % polygon.len())))
+    }).collect()
+}
+
+#[test]
+fn every_edge_of_the_cube_is_paired_with_its_opposite() {
+    let geometry = cube();
+    let neighbours = geometry.neighbour_layer().as_vec_i32().clone();
+    let edges = edges(&geometry);
+    assert_eq!(neighbours.len(), 24);
+
+    for (corner, neighbour) in neighbours.iter().enumerate() {
+        assert_ne!(*neighbour, NO_NEIGHBOUR, "Expected corner {} to have a neighbour", corner);
+        let neighbour = *neighbour as usize;
+        assert_eq!(neighbours[neighbour] as usize, corner);
+
+        let (from, to) = edges[corner];
+        assert_eq!(edges[neighbour], (to, from));
+    }
+}
+
+#[test]
+fn boundary_edges_have_no_neighbour() {
+    // Two triangles sharing the diagonal of a square, the edge 2 -> 0 of one is 0 -> 2 of the other
+    let references = [0, 1, -3, 0, 2, -4];
+    assert_eq!(compute_neighbours(&references), vec![NO_NEIGHBOUR, NO_NEIGHBOUR, 3, 2, NO_NEIGHBOUR, NO_NEIGHBOUR]);
+}
+
+#[test]
+fn edges_shared_by_more_than_two_polygons_pair_in_corner_order() {
+    // All three triangles share the edge between vertices 0 and 1, the third runs it the other way
+    let references = [0, 1, -3, 0, 1, -4, 1, 0, -5];
+    let mut expected = vec![NO_NEIGHBOUR; 9];
+    expected[0] = 6;
+    expected[6] = 0;
+    assert_eq!(compute_neighbours(&references), expected);
+}
+
+#[test]
+fn neighbour_layer_is_added_once() {
+    let mut geometry = cube();
+    assert!(geometry.add_neighbour_layer().expect("Expected a reference layer"));
+    assert_eq!(geometry.edge_stack.layers.len(), 1);
+    assert_eq!(geometry.edge_stack.layers[0].name, "neighbour");
+
+    assert!(!geometry.add_neighbour_layer().expect("Expected a reference layer"));
+    assert_eq!(geometry.edge_stack.layers.len(), 1);
+    assert!(geometry.is_valid());
+}
+
+#[test]
+fn neighbour_layer_needs_a_usable_reference_layer() {
+    let mut without_references = cube();
+    without_references.corner_stack = hxa::HXALayerStack::new();
+    let err = without_references.add_neighbour_layer().expect_err("Expected the reference layer to be missing");
+    assert!(matches!(err, HXAError::MissingLayer(name) if name == "reference"));
+
+    let mut wrong_length = polygon_mesh(&[0.0; 9], &[0, 1, -3]);
+    wrong_length.edge_corner_count = 4;
+    let err = wrong_length.add_neighbour_layer().expect_err("Expected the reference layer to be rejected");
+    assert!(matches!(err, HXAError::InvalidLayer { layer, .. } if layer == "reference"));
+    assert!(wrong_length.edge_stack.layers.is_empty());
+}
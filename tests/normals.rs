@@ -0,0 +1,118 @@
+use hxa::enums::HXALayerDataType;
+use hxa::normals::{HXAHardEdges, HXANormalWeighting};
+use hxa::HXAError;
+
+mod common;
+use common::{cube, layer};
+
+/// The outward normal of each face of the cube, in the order of `CUBE_QUADS`
+const CUBE_FACE_NORMALS: [[f32; 3]; 6] = [
+    [-1.0, 0.0, 0.0],
+    [1.0, 0.0, 0.0],
+    [0.0, -1.0, 0.0],
+    [0.0, 1.0, 0.0],
+    [0.0, 0.0, -1.0],
+    [0.0, 0.0, 1.0],
+];
+
+const WEIGHTINGS: [HXANormalWeighting; 2] = [HXANormalWeighting::Area, HXANormalWeighting::Angle];
+
+fn assert_close(found: [f32; 3], expected: [f32; 3]) {
+    assert!(
+        found.iter().zip(expected).all(|(found, expected)| (found - expected).abs() < 1e-6),
+        "Expected {:?} but found {:?}", expected, found
+    );
+}
+
+#[test]
+fn face_normals_of_the_cube_are_axis_aligned() {
+    let normals = cube().face_normals();
+    assert_eq!(normals.len(), 6);
+    for (normal, expected) in normals.iter().zip(CUBE_FACE_NORMALS) {
+        assert_close(*normal, expected);
+    }
+}
+
+#[test]
+fn vertex_normals_of_the_cube_point_out_of_the_corners() {
+    let geometry = cube();
+    let third = 1.0 / 3.0f32.sqrt();
+    for weighting in WEIGHTINGS {
+        let normals = geometry.vertex_normals(weighting);
+        assert_eq!(normals.len(), 8);
+        assert_close(normals[0], [-third, -third, -third]);
+        assert_close(normals[7], [third, third, third]);
+    }
+}
+
+#[test]
+fn corner_normals_split_at_sharp_angles() {
+    let geometry = cube();
+    let hard_edges = HXAHardEdges { creases: false, angle: Some(30.0) };
+    for weighting in WEIGHTINGS {
+        let normals = geometry.corner_normals(weighting, hard_edges);
+        assert_eq!(normals.len(), 24);
+        for (corner, normal) in normals.iter().enumerate() {
+            assert_close(*normal, CUBE_FACE_NORMALS[corner / 4]);
+        }
+    }
+
+    // Without hard edges the corners of a closed surface match the vertex normals
+    let smooth = geometry.corner_normals(HXANormalWeighting::Angle, HXAHardEdges::default());
+    let vertex_normals = geometry.vertex_normals(HXANormalWeighting::Angle);
+    for (corner, vertex) in geometry.polygons().flat_map(|polygon| polygon.corners.clone().zip(polygon.vertices())) {
+        assert_close(smooth[corner], vertex_normals[vertex as usize]);
+    }
+}
+
+#[test]
+fn corner_normals_split_only_at_creases() {
+    let mut geometry = cube();
+    // Crease the four edges around the +z face, seen from that face only
+    let mut creases = vec![0.0f32; 24];
+    creases[20..24].fill(1.0);
+    geometry.edge_stack.push(layer("creases", 1, HXALayerDataType::FLOAT(creases)));
+
+    let normals = geometry.corner_normals(HXANormalWeighting::Angle, HXAHardEdges { creases: true, angle: None });
+    let third = 1.0 / 3.0f32.sqrt();
+    let half = 1.0 / 2.0f32.sqrt();
+
+    // The +z face is cut off from its neighbours
+    for normal in &normals[20..24] {
+        assert_close(*normal, [0.0, 0.0, 1.0]);
+    }
+    // Vertex 0 touches no creased edge, vertex 4 is on the crease where -x and -y stay joined
+    assert_close(normals[0], [-third, -third, -third]);
+    assert_close(normals[1], [-half, -half, 0.0]);
+    assert_close(normals[11], [-half, -half, 0.0]);
+
+    // Creases are ignored unless asked for
+    let smooth = geometry.corner_normals(HXANormalWeighting::Angle, HXAHardEdges::default());
+    assert_close(smooth[20], [-third, -third, third]);
+}
+
+#[test]
+fn reference_layer_longer_than_the_corner_count_is_rejected() {
+    let mut geometry = cube();
+    geometry.edge_corner_count = 20;
+    geometry.edge_stack.push(layer("creases", 1, HXALayerDataType::FLOAT(vec![1.0; 20])));
+
+    let hard_edges = HXAHardEdges { creases: true, angle: None };
+    assert!(geometry.try_corner_normals(HXANormalWeighting::Area, hard_edges).is_some());
+
+    let err = geometry.set_corner_normals(HXANormalWeighting::Area, hard_edges).expect_err("Expected the reference layer to be rejected");
+    assert!(matches!(err, HXAError::InvalidLayer { layer, .. } if layer == "reference"));
+    assert!(geometry.corner_stack.find("normal").is_none());
+}
+
+#[test]
+fn set_normals_replaces_the_layer_in_the_other_stack() {
+    let mut geometry = cube();
+    geometry.set_vertex_normals(HXANormalWeighting::Area).expect("Expected a reference and a vertex layer");
+    assert_eq!(geometry.vertex_stack.find("normal").expect("Expected a normal layer").as_vec_f32().len(), 24);
+
+    geometry.set_corner_normals(HXANormalWeighting::Area, HXAHardEdges::default()).expect("Expected a reference and a vertex layer");
+    assert!(geometry.vertex_stack.find("normal").is_none());
+    assert_eq!(geometry.corner_stack.find("normal").expect("Expected a normal layer").as_vec_f32().len(), 72);
+    assert!(geometry.is_valid());
+}
@@ -0,0 +1,55 @@
+use hxa::polygon::decode_reference;
+use hxa::HXAPolygons;
+
+mod common;
+use common::cube;
+
+#[test]
+fn decode_reference_handles_every_sign() {
+    assert_eq!(decode_reference(0), 0);
+    assert_eq!(decode_reference(7), 7);
+    assert_eq!(decode_reference(-1), 0);
+    assert_eq!(decode_reference(-8), 7);
+    assert_eq!(decode_reference(i32::MIN), i32::MAX as u32);
+}
+
+#[test]
+fn polygons_of_mixed_triangles_and_quads() {
+    // A triangle, a quad, then two corners that are never closed
+    let references = [0, 1, -3, 2, 3, 4, -6, 5, 6];
+    let polygons: Vec<_> = HXAPolygons::new(&references).collect();
+    assert_eq!(polygons.len(), 3);
+
+    assert_eq!(polygons[0].face, 0);
+    assert_eq!(polygons[0].corners, 0..3);
+    assert_eq!(polygons[0].vertices().collect::<Vec<_>>(), vec![0, 1, 2]);
+    assert!(polygons[0].is_closed());
+
+    assert_eq!(polygons[1].face, 1);
+    assert_eq!(polygons[1].corners, 3..7);
+    assert_eq!(polygons[1].len(), 4);
+    assert_eq!(polygons[1].vertex(3), 5);
+    assert!(polygons[1].is_closed());
+
+    assert_eq!(polygons[2].face, 2);
+    assert_eq!(polygons[2].corners, 7..9);
+    assert_eq!(polygons[2].vertices().collect::<Vec<_>>(), vec![5, 6]);
+    assert!(!polygons[2].is_closed());
+}
+
+#[test]
+fn polygons_of_the_cube() {
+    let geometry = cube();
+    let mut polygons = 0;
+    for (polygon, quad) in geometry.polygons().zip(common::CUBE_QUADS) {
+        let quad: Vec<u32> = quad.iter().map(|vertex| *vertex as u32).collect();
+        assert_eq!(polygon.vertices().collect::<Vec<_>>(), quad);
+        assert_eq!(polygon.corners, polygon.face as usize * 4..polygon.face as usize * 4 + 4);
+        polygons += 1;
+    }
+    assert_eq!(polygons, 6);
+
+    let mut without_references = cube();
+    without_references.corner_stack = hxa::HXALayerStack::new();
+    assert!(without_references.try_polygons().is_none());
+}
@@ -0,0 +1,156 @@
+use hxa::enums::{HXALayerDataType, HXAMetaDataType, HXANodeType};
+use hxa::{HXAError, HXAFile, HXAWarning, LoadOptions};
+
+mod common;
+use common::{image_bytes, push_u32, triangle_bytes};
+
+fn write_to_vec(file: &HXAFile) -> Vec<u8> {
+    let mut written = Vec::new();
+    file.write_to(&mut written).expect("Expected the file to be writable");
+    written
+}
+
+#[test]
+fn read_write_read_is_byte_identical() {
+    let original = triangle_bytes();
+
+    let parsed = HXAFile::from_bytes(&original).expect("Expected a valid file");
+    let written = write_to_vec(&parsed);
+    assert_eq!(original, written);
+
+    let reparsed = HXAFile::from_bytes(&written).expect("Expected the written file to be valid");
+    assert_eq!(original, write_to_vec(&reparsed));
+}
+
+#[test]
+fn write_recomputes_stale_counts() {
+    let mut parsed = HXAFile::from_bytes(&triangle_bytes()).expect("Expected a valid file");
+
+    parsed.node_count = 40;
+    let node = &mut parsed.node_array[0];
+    node.metadata_count = 0;
+    node.meta_data[0].data_length = 1000;
+    if let HXAMetaDataType::META { meta_array } = &mut node.meta_data[1].meta_type {
+        meta_array[0].data_length = 0;
+    }
+    if let HXANodeType::Geometry(geometry) = &mut node.node_type {
+        geometry.vertex_stack.layer_count = 12;
+        geometry.edge_stack.layer_count = 3;
+    }
+
+    assert_eq!(triangle_bytes(), write_to_vec(&parsed));
+}
+
+#[test]
+fn write_rejects_layers_that_do_not_match_the_count() {
+    let mut parsed = HXAFile::from_bytes(&triangle_bytes()).expect("Expected a valid file");
+
+    if let HXANodeType::Geometry(geometry) = &mut parsed.node_array[0].node_type {
+        geometry.vertex_count = 4;
+    }
+
+    let err = parsed.write_to(Vec::new()).expect_err("Expected the vertex layer to be too short");
+    assert_eq!(err.path(), Some("node[0].geometry.vertex_stack.layer[\"vertex\"]"));
+}
+
+#[test]
+fn image_nodes_and_meta_payloads_round_trip() {
+    let original = image_bytes();
+
+    let parsed = HXAFile::from_bytes(&original).expect("Expected a valid file");
+    match &parsed.node_array[0].meta_data[1].meta_type {
+        HXAMetaDataType::NODE { node_array } => assert_eq!(node_array, &vec![1]),
+        _ => panic!("Expected node meta data"),
+    }
+    match &parsed.node_array[1].node_type {
+        HXANodeType::Image(image) => {
+            assert_eq!(image.pixel_count(), 6);
+            assert_eq!(image.image_stack.find("light").map(|layer| layer.as_vec_f32().len()), Some(6));
+        },
+        _ => panic!("Expected an image node"),
+    }
+
+    assert_eq!(original, write_to_vec(&parsed));
+}
+
+#[test]
+fn large_arrays_read_back_value_for_value() {
+    let mut parsed = HXAFile::from_bytes(&triangle_bytes()).expect("Expected a valid file");
+
+    let vertex_count = 100_000;
+    let positions: Vec<f32> = (0..vertex_count * 3).map(|index| index as f32 * 0.25 - 1000.0).collect();
+    let scales: Vec<f64> = (0..5000).map(|index| index as f64 / 3.0).collect();
+    let node = &mut parsed.node_array[0];
+    if let HXAMetaDataType::META { meta_array } = &mut node.meta_data[1].meta_type {
+        meta_array[0].meta_type = HXAMetaDataType::DOUBLE { double_array: scales.clone() };
+    }
+    if let HXANodeType::Geometry(geometry) = &mut node.node_type {
+        geometry.vertex_count = vertex_count;
+        geometry.vertex_stack.layers[0].layer_type = HXALayerDataType::FLOAT(positions.clone());
+    }
+    let bytes = write_to_vec(&parsed);
+
+    // Loaded from a slice, whose length is known, and from a reader, whose length is not
+    for loaded in [HXAFile::from_bytes(&bytes), HXAFile::from_reader(bytes.as_slice())] {
+        let loaded = loaded.expect("Expected a valid file");
+        let (geometry, meta_data) = loaded.get_first_geometry().expect("Expected a geometry node");
+        assert_eq!(geometry.vertex_stack.find("vertex").map(|layer| layer.as_vec_f32()), Some(&positions));
+        assert_eq!(geometry.corner_stack.find("reference").map(|layer| layer.as_vec_i32()), Some(&vec![0, 1, -3]));
+        match &meta_data[1].meta_type {
+            HXAMetaDataType::META { meta_array } => {
+                assert!(matches!(&meta_array[0].meta_type, HXAMetaDataType::DOUBLE { double_array } if *double_array == scales));
+            },
+            _ => panic!("Expected nested meta data"),
+        }
+        assert_eq!(write_to_vec(&loaded), bytes);
+    }
+}
+
+#[test]
+fn unknown_final_node_is_kept_unchanged() {
+    // The triangle followed by a node from a newer exporter
+    let mut original = triangle_bytes();
+    original[5..9].copy_from_slice(&2u32.to_le_bytes());
+    original.push(9);
+    push_u32(&mut original, 0);
+    original.extend_from_slice(&[1, 2, 3, 4, 5]);
+
+    assert!(HXAFile::from_bytes(&original).is_err());
+
+    let (parsed, warnings) = HXAFile::from_bytes_lenient(&original).expect("Expected the unknown node to be kept");
+    assert_eq!(warnings.len(), 1);
+    match &parsed.node_array[1].node_type {
+        HXANodeType::Unsupported { type_code, payload } => {
+            assert_eq!(*type_code, 9);
+            assert_eq!(payload, &vec![1, 2, 3, 4, 5]);
+        },
+        _ => panic!("Expected an unsupported node"),
+    }
+
+    assert_eq!(original, write_to_vec(&parsed));
+}
+
+#[test]
+fn unknown_node_before_others_is_not_dropped_silently() {
+    // A node from a newer exporter followed by the triangle
+    let triangle = triangle_bytes();
+    let mut original = triangle[..9].to_vec();
+    original[5..9].copy_from_slice(&2u32.to_le_bytes());
+    original.push(9);
+    push_u32(&mut original, 0);
+    original.extend_from_slice(&triangle[9..]);
+
+    let (parsed, warnings) = HXAFile::from_bytes_lenient(&original).expect("Expected loading to stop before the unknown node");
+    assert!(parsed.node_array.is_empty());
+    assert!(matches!(warnings[..], [HXAWarning::UnknownNodeSkipped { node_index: 0, type_code: 9, offset: 9, nodes_after: 1 }]));
+
+    // Without a way to return the warning the load fails rather than losing the triangle
+    let options = LoadOptions { keep_unknown_nodes: true, ..Default::default() };
+    let err = HXAFile::from_bytes_with(&original, options.clone()).expect_err("Expected the skipped node to be reported");
+    assert!(matches!(err.root(), HXAError::UnknownNodeType(9)));
+    assert_eq!(err.offset(), Some(9));
+    assert_eq!(err.path(), Some("node[0]"));
+
+    let err = HXAFile::from_reader_with(original.as_slice(), options).expect_err("Expected the skipped node to be reported");
+    assert!(matches!(err.root(), HXAError::UnknownNodeType(9)));
+}
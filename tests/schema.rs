@@ -0,0 +1,87 @@
+use hxa::enums::{HXALayerDataType, HXALayerValueType, HXANodeType};
+use hxa::schema::{HXALayerConvention, HXASchemaProblem, HXAStack};
+use hxa::{HXAFile, HXAGeometryNode, HXASchema, HXASeverity};
+
+mod common;
+use common::layer;
+
+/// The cube as a file, with `change` applied to its geometry
+fn cube_file(change: impl FnOnce(&mut HXAGeometryNode)) -> HXAFile {
+    let mut file = HXAFile::from_bytes(&common::cube_bytes()).expect("Expected a valid file");
+    match &mut file.node_array[0].node_type {
+        HXANodeType::Geometry(geometry) => change(geometry),
+        _ => panic!("Expected a geometry node"),
+    }
+    file
+}
+
+#[test]
+fn cube_follows_the_standard_schema() {
+    let file = cube_file(|_| ());
+    assert!(HXASchema::standard().validate(&file).is_empty());
+}
+
+#[test]
+fn normal_of_the_wrong_type_and_components_gives_two_warnings() {
+    let file = cube_file(|geometry| {
+        geometry.vertex_stack.layers.push(layer("normal", 2, HXALayerDataType::UINT8(vec![0; 16])));
+    });
+
+    let issues = HXASchema::standard().validate(&file);
+    assert_eq!(issues.len(), 2);
+    for issue in &issues {
+        assert_eq!(issue.node_index, 0);
+        assert_eq!(issue.stack, HXAStack::Vertex);
+        assert_eq!(issue.layer, "normal");
+        assert_eq!(issue.severity, HXASeverity::Warning);
+    }
+    assert_eq!(issues[0].problem, HXASchemaProblem::WrongType { found: Some(HXALayerValueType::UINT8) });
+    assert_eq!(issues[1].problem, HXASchemaProblem::WrongComponents { found: 2 });
+    assert_eq!(issues[1].to_string(), "node[0].geometry.vertex_stack.layer[\"normal\"] has 2 components, expected 3");
+}
+
+#[test]
+fn missing_reference_is_an_error() {
+    let file = cube_file(|geometry| {
+        geometry.corner_stack.layers.retain(|layer| layer.name != "reference");
+    });
+
+    let issues = HXASchema::hard().validate(&file);
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0].stack, HXAStack::Corner);
+    assert_eq!(issues[0].layer, "reference");
+    assert_eq!(issues[0].problem, HXASchemaProblem::Missing);
+    assert_eq!(issues[0].severity, HXASeverity::Error);
+}
+
+#[test]
+fn layer_in_the_wrong_stack_is_reported() {
+    let file = cube_file(|geometry| {
+        geometry.face_stack.layers.push(layer("normal", 3, HXALayerDataType::FLOAT(vec![0.0; 18])));
+    });
+
+    let issues = HXASchema::standard().validate(&file);
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0].stack, HXAStack::Face);
+    assert_eq!(issues[0].problem, HXASchemaProblem::WrongStack);
+}
+
+#[test]
+fn register_replaces_an_existing_convention() {
+    let file = cube_file(|geometry| {
+        geometry.vertex_stack.layers.push(layer("normal", 2, HXALayerDataType::UINT8(vec![0; 16])));
+    });
+
+    let mut profile = HXASchema::standard();
+    let conventions = profile.conventions.len();
+    profile.register(HXALayerConvention {
+        severity: HXASeverity::Error,
+        ..HXALayerConvention::new("normal", &[HXAStack::Vertex], &[HXALayerValueType::UINT8], Some(2))
+    });
+
+    assert_eq!(profile.conventions.len(), conventions);
+    let normal = profile.find("normal").expect("Expected a normal convention");
+    assert_eq!(normal.types, vec![HXALayerValueType::UINT8]);
+    assert_eq!(normal.severity, HXASeverity::Error);
+    assert!(profile.validate(&file).is_empty());
+}
@@ -0,0 +1,99 @@
+use hxa::polygon::decode_reference;
+use hxa::{HXAGeometryNode, HXATriangles, HXATriangulation};
+
+mod common;
+use common::polygon_mesh;
+
+/// An L shape of area 3 in the z = 0 plane, wound counter clockwise and starting next to its concave corner
+const L_SHAPE: [[f32; 2]; 6] = [[2.0, 1.0], [1.0, 1.0], [1.0, 2.0], [0.0, 2.0], [0.0, 0.0], [2.0, 0.0]];
+
+fn l_shape(clockwise: bool) -> HXAGeometryNode {
+    let mut points = L_SHAPE.to_vec();
+    if clockwise {
+        points.reverse();
+    }
+    let positions: Vec<f32> = points.iter().flat_map(|[x, y]| [*x, *y, 0.0]).collect();
+    polygon_mesh(&positions, &[0, 1, 2, 3, 4, -6])
+}
+
+/// Signed area of every triangle seen from +z, positive when it winds counter clockwise
+fn signed_areas(geometry: &HXAGeometryNode, triangles: &HXATriangles) -> Vec<f32> {
+    let positions = geometry.vertex_stack.layers[0].as_vec_f32();
+    let point = |index: u32| [positions[index as usize * 3], positions[index as usize * 3 + 1]];
+    triangles.indices.chunks_exact(3).map(|triangle| {
+        let (a, b, c) = (point(triangle[0]), point(triangle[1]), point(triangle[2]));
+        ((b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0])) / 2.0
+    }).collect()
+}
+
+/// Checks that every triangle corner points back at a corner of its polygon holding the same vertex
+fn assert_maps_back(geometry: &HXAGeometryNode, triangles: &HXATriangles) {
+    let references = geometry.corner_stack.layers[0].as_vec_i32();
+    let polygons: Vec<_> = geometry.polygons().collect();
+    assert_eq!(triangles.triangle_corners.len(), triangles.indices.len());
+
+    for (entry, vertex) in triangles.indices.iter().enumerate() {
+        let corner = triangles.triangle_corners[entry] as usize;
+        let face = triangles.triangle_faces[entry / 3] as usize;
+        assert_eq!(decode_reference(references[corner]), *vertex);
+        assert!(polygons[face].corners.contains(&corner));
+    }
+}
+
+#[test]
+fn ear_clipping_splits_a_concave_polygon_either_way_round() {
+    for clockwise in [false, true] {
+        let geometry = l_shape(clockwise);
+        let triangles = geometry.triangulate(HXATriangulation::EarClipping);
+        assert_eq!(triangles.len(), 4);
+        assert_maps_back(&geometry, &triangles);
+
+        // Triangles keep the winding of the polygon, so none of them is inverted
+        let areas = signed_areas(&geometry, &triangles);
+        let sign = if clockwise { -1.0 } else { 1.0 };
+        assert!(areas.iter().all(|area| area * sign > 0.0), "Expected no inverted triangle in {:?}", areas);
+        assert!((areas.iter().sum::<f32>() - 3.0 * sign).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn fan_inverts_triangles_of_a_concave_polygon() {
+    // The reason ear clipping exists, the fan from the first corner crosses the concave corner
+    let geometry = l_shape(false);
+    let triangles = geometry.triangulate(HXATriangulation::Fan);
+    assert_eq!(triangles.len(), 4);
+    assert!(signed_areas(&geometry, &triangles).iter().any(|area| *area < 0.0));
+}
+
+#[test]
+fn fan_splits_a_quad_from_its_first_corner() {
+    let geometry = polygon_mesh(&[0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0, 0.0], &[0, 1, 2, -4]);
+    let triangles = geometry.triangulate(HXATriangulation::Fan);
+    assert_eq!(triangles.indices, vec![0, 1, 2, 0, 2, 3]);
+    assert_eq!(triangles.triangle_corners, vec![0, 1, 2, 0, 2, 3]);
+    assert_eq!(triangles.triangle_faces, vec![0, 0]);
+}
+
+#[test]
+fn triangles_of_the_cube_map_back_to_faces_and_corners() {
+    let geometry = common::cube();
+    for mode in [HXATriangulation::Fan, HXATriangulation::EarClipping] {
+        let triangles = geometry.triangulate(mode);
+        assert_eq!(triangles.len(), 12);
+        assert_eq!(triangles.triangle_faces, vec![0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5]);
+        assert_maps_back(&geometry, &triangles);
+    }
+}
+
+#[test]
+fn short_polygons_are_left_out_and_missing_layers_fail() {
+    let geometry = polygon_mesh(&[0.0; 9], &[0, -2, 0, 1, -3]);
+    let triangles = geometry.triangulate(HXATriangulation::Fan);
+    assert_eq!(triangles.triangle_faces, vec![1]);
+    assert_eq!(triangles.triangle_corners, vec![2, 3, 4]);
+
+    let mut without_vertices = common::cube();
+    without_vertices.vertex_stack = hxa::HXALayerStack::new();
+    assert!(without_vertices.try_triangulate(HXATriangulation::Fan).is_some());
+    assert!(without_vertices.try_triangulate(HXATriangulation::EarClipping).is_none());
+}
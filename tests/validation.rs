@@ -0,0 +1,113 @@
+use hxa::enums::HXALayerDataType;
+use hxa::{HXAGeometryIssue, HXASeverity};
+
+mod common;
+use common::{cube, layer, references_mut};
+
+#[test]
+fn cube_is_valid() {
+    let geometry = cube();
+    assert_eq!(geometry.validate(), Vec::new());
+    assert!(geometry.is_valid());
+}
+
+#[test]
+fn missing_vertex_and_reference_layers_are_errors() {
+    let mut geometry = cube();
+    geometry.vertex_stack.layers[0].name = String::from("position");
+    geometry.corner_stack.layers[0].name = String::from("index");
+
+    let issues = geometry.validate();
+    assert!(issues.contains(&HXAGeometryIssue::MissingVertexLayer));
+    assert!(issues.contains(&HXAGeometryIssue::MissingReferenceLayer));
+    assert!(!geometry.is_valid());
+}
+
+#[test]
+fn bad_vertex_layer_is_an_error() {
+    let mut geometry = cube();
+    geometry.vertex_stack.layers[0] = layer("vertex", 3, HXALayerDataType::INT32(vec![0; 24]));
+
+    let issues = geometry.validate();
+    assert_eq!(issues, vec![HXAGeometryIssue::BadVertexLayer { components: 3, type_code: Some(1) }]);
+    assert_eq!(issues[0].severity(), HXASeverity::Error);
+}
+
+#[test]
+fn bad_reference_layer_is_an_error() {
+    let mut geometry = cube();
+    geometry.corner_stack.layers[0] = layer("reference", 1, HXALayerDataType::FLOAT(vec![0.0; 24]));
+
+    // The polygons cannot be followed, so only the layer itself is reported
+    let issues = geometry.validate();
+    assert_eq!(issues, vec![HXAGeometryIssue::BadReferenceLayer { components: 1, type_code: Some(2) }]);
+}
+
+#[test]
+fn layer_of_the_wrong_length_is_an_error() {
+    let mut geometry = cube();
+    geometry.face_stack.layers.push(layer("material", 1, HXALayerDataType::INT32(vec![0; 5])));
+
+    let issues = geometry.validate();
+    assert_eq!(issues, vec![HXAGeometryIssue::LayerLength { stack: "face_stack", layer: String::from("material"), expected: 6, found: 5 }]);
+}
+
+#[test]
+fn duplicate_layer_name_is_a_warning() {
+    let mut geometry = cube();
+    geometry.face_stack.layers.push(layer("material", 1, HXALayerDataType::INT32(vec![0; 6])));
+    geometry.face_stack.layers.push(layer("material", 1, HXALayerDataType::INT32(vec![1; 6])));
+
+    let issues = geometry.validate();
+    assert_eq!(issues, vec![HXAGeometryIssue::DuplicateLayerName { stack: "face_stack", layer: String::from("material") }]);
+    assert_eq!(issues[0].severity(), HXASeverity::Warning);
+    assert!(geometry.is_valid());
+}
+
+#[test]
+fn reference_out_of_range_gives_the_first_corner_and_the_count() {
+    let mut geometry = cube();
+    references_mut(&mut geometry)[5] = 8;
+    references_mut(&mut geometry)[23] = -11;
+
+    let issues = geometry.validate();
+    assert_eq!(issues, vec![HXAGeometryIssue::ReferenceOutOfRange { corner: 5, vertex: 8, count: 2 }]);
+}
+
+#[test]
+fn open_last_polygon_is_an_error() {
+    let mut geometry = cube();
+    references_mut(&mut geometry)[23] = 6;
+
+    // Only closed polygons are counted, so the faces no longer add up either
+    let issues = geometry.validate();
+    assert_eq!(issues, vec![
+        HXAGeometryIssue::OpenPolygon,
+        HXAGeometryIssue::FaceCountMismatch { polygons: 5, face_count: 6 },
+    ]);
+}
+
+#[test]
+fn short_polygon_is_a_warning() {
+    let mut geometry = cube();
+    // Closing the first quad after 2 corners splits it into two polygons of 2 corners
+    references_mut(&mut geometry)[1] = -5;
+    geometry.face_count = 7;
+    geometry.face_stack = hxa::HXALayerStack::new();
+
+    let issues = geometry.validate();
+    assert_eq!(issues, vec![
+        HXAGeometryIssue::ShortPolygon { face: 0, corners: 2 },
+        HXAGeometryIssue::ShortPolygon { face: 1, corners: 2 },
+    ]);
+    assert!(geometry.is_valid());
+}
+
+#[test]
+fn face_count_mismatch_is_an_error() {
+    let mut geometry = cube();
+    geometry.face_count = 5;
+
+    let issues = geometry.validate();
+    assert_eq!(issues, vec![HXAGeometryIssue::FaceCountMismatch { polygons: 6, face_count: 5 }]);
+}
@@ -0,0 +1,167 @@
+use hxa::enums::HXANodeType;
+use hxa::view::{HXALayerStackView, HXALayerView, HXANodeViewType};
+use hxa::{HXAFile, HXAFileView, HXALayerStack, LoadFilter, LoadOptions};
+
+mod common;
+
+/// Checks that a borrowed layer holds the same values as the loaded one
+fn assert_same_values(view: &HXALayerView, layer: &hxa::HXALayer) {
+    assert_eq!(view.name, layer.name);
+    assert_eq!(view.components, layer.components);
+    if let Some(values) = layer.try_as_vec_u8() {
+        assert_eq!(view.as_slice_u8(), values.as_slice());
+    } else if let Some(values) = layer.try_as_vec_i32() {
+        assert_eq!(view.as_slice_i32(), values.as_slice());
+    } else if let Some(values) = layer.try_as_vec_f32() {
+        assert_eq!(view.as_slice_f32(), values.as_slice());
+    } else {
+        assert_eq!(view.as_slice_f64(), layer.as_vec_f64().as_slice());
+    }
+}
+
+fn assert_same_stack(view: &HXALayerStackView, stack: &HXALayerStack) {
+    assert_eq!(view.layers.len(), stack.layers.len());
+    for (view, layer) in view.layers.iter().zip(&stack.layers) {
+        assert_same_values(view, layer);
+    }
+}
+
+#[test]
+fn view_matches_loaded_file_whether_borrowed_or_copied() {
+    let bytes = common::triangle_bytes();
+    let loaded = HXAFile::from_bytes(&bytes).expect("Expected a valid file");
+    let geometry = loaded.get_first_geometry().expect("Expected a geometry node").0;
+
+    // Backed by u64 so the start is 8 byte aligned, each shift moves the layers to a different alignment
+    let mut storage = vec![0u64; bytes.len() / 8 + 2];
+    let mut borrowed = Vec::new();
+    for shift in 0..8 {
+        let backing: &mut [u8] = as_bytes_mut(&mut storage);
+        backing[shift..shift + bytes.len()].copy_from_slice(&bytes);
+        let shifted = &backing[shift..shift + bytes.len()];
+
+        let view = HXAFileView::from_bytes(shifted).expect("Expected a valid file");
+        let geometry_view = view.get_first_geometry().expect("Expected a geometry node").0;
+        assert_same_stack(&geometry_view.vertex_stack, &geometry.vertex_stack);
+        assert_same_stack(&geometry_view.corner_stack, &geometry.corner_stack);
+        assert_same_stack(&geometry_view.face_stack, &geometry.face_stack);
+
+        let vertex = geometry_view.vertex_stack.find("vertex").expect("Expected a vertex layer");
+        let aligned = vertex.as_slice_f32().as_ptr().is_aligned();
+        if vertex.is_borrowed() {
+            assert!(aligned);
+            // The vertex values follow the header, both meta data and the layer header
+            assert!(std::ptr::eq(vertex.as_slice_f32().as_ptr() as *const u8, shifted[79..].as_ptr()));
+        }
+        borrowed.push(vertex.is_borrowed());
+    }
+
+    if cfg!(target_endian = "little") {
+        assert!(borrowed.contains(&true), "Expected an aligned layer to be borrowed");
+    }
+    assert!(borrowed.contains(&false), "Expected an unaligned layer to be copied");
+}
+
+fn as_bytes_mut(storage: &mut [u64]) -> &mut [u8] {
+    // Safety: u8 has no alignment requirement and every bit pattern of u64 is a valid sequence of bytes
+    unsafe { std::slice::from_raw_parts_mut(storage.as_mut_ptr() as *mut u8, storage.len() * 8) }
+}
+
+#[test]
+fn view_applies_load_filter() {
+    let bytes = common::mixed_bytes();
+    let options = LoadOptions {
+        filter: LoadFilter {
+            node_types: Some(vec![1, 2]),
+            node_indices: Some(vec![0, 2]),
+            layer_names: Some(vec![String::from("vertex"), String::from("light")]),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let loaded = HXAFile::from_bytes_with(&bytes, options.clone()).expect("Expected a valid file");
+    let view = HXAFileView::from_bytes_with(&bytes, options).expect("Expected a valid file");
+    assert_eq!(view.node_count, loaded.node_count);
+    assert_eq!(view.node_array.len(), 2);
+
+    for (node_view, node) in view.node_array.iter().zip(&loaded.node_array) {
+        match (&node_view.node_type, &node.node_type) {
+            (HXANodeViewType::Geometry(geometry_view), HXANodeType::Geometry(geometry)) => {
+                assert_same_stack(&geometry_view.vertex_stack, &geometry.vertex_stack);
+                assert!(geometry_view.corner_stack.layers.is_empty());
+                assert!(geometry_view.face_stack.layers.is_empty());
+            },
+            (HXANodeViewType::Image(image_view), HXANodeType::Image(image)) => {
+                assert_eq!(image_view.resolution, image.resolution);
+                assert_same_stack(&image_view.image_stack, &image.image_stack);
+                assert_eq!(image_view.image_stack.layers.len(), 1);
+            },
+            _ => panic!("Expected the view and the file to hold the same nodes"),
+        }
+    }
+}
+
+#[test]
+fn borrowed_slices_outlive_the_view() {
+    let bytes = common::triangle_bytes();
+    let mut storage = vec![0u64; bytes.len() / 8 + 2];
+    let backing = as_bytes_mut(&mut storage);
+    // The vertex values start 79 bytes into the file, a shift of 1 puts them on a 4 byte boundary
+    backing[1..1 + bytes.len()].copy_from_slice(&bytes);
+    let shifted: &[u8] = &backing[1..1 + bytes.len()];
+
+    let (vertices, uv) = {
+        let view = HXAFileView::from_bytes(shifted).expect("Expected a valid file");
+        let geometry = view.get_first_geometry().expect("Expected a geometry node").0;
+        let vertex = geometry.vertex_stack.find("vertex").expect("Expected a vertex layer");
+        let uv = geometry.corner_stack.find("uv").expect("Expected a uv layer");
+        assert!(vertex.try_borrowed_i32().is_none());
+        (vertex.try_borrowed_f32(), uv.try_borrowed_u8().expect("Expected UINT8 layers to be borrowed"))
+    };
+
+    if cfg!(target_endian = "little") {
+        assert_eq!(vertices, Some(&[0.0f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0][..]));
+    }
+    assert_eq!(uv, &[0, 0, 255, 0, 0, 255]);
+}
+
+#[cfg(feature = "mmap")]
+#[test]
+fn mapped_file_views_the_same_layers_under_limits_and_filters() {
+    let bytes = common::triangle_bytes();
+    let path = common::temp_file("view", &bytes);
+
+    // Safety: the file is private to this test and not modified while mapped
+    let mapped = unsafe { hxa::HXAMappedFile::open(&path) }.expect("Expected to map the file");
+    assert_eq!(mapped.bytes(), bytes.as_slice());
+    let view = mapped.view().expect("Expected a valid file");
+    let loaded = HXAFile::from_bytes(&bytes).expect("Expected a valid file");
+    let geometry = loaded.get_first_geometry().expect("Expected a geometry node").0;
+    assert_same_stack(&view.get_first_geometry().expect("Expected a geometry node").0.vertex_stack, &geometry.vertex_stack);
+
+    let limited = LoadOptions {
+        max_layers: 1,
+        ..Default::default()
+    };
+    let err = mapped.view_with(limited).expect_err("Expected the corner layers to be over the limit");
+    assert!(matches!(err.root(), hxa::HXAError::LimitExceeded { limit: "max_layers", .. }));
+    assert_eq!(err.offset(), Some(119));
+
+    let filtered = LoadOptions {
+        filter: LoadFilter {
+            layer_names: Some(vec![String::from("vertex")]),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let filtered = mapped.view_with(filtered).expect("Expected a valid file");
+    let geometry_view = filtered.get_first_geometry().expect("Expected a geometry node").0;
+    assert_same_stack(&geometry_view.vertex_stack, &geometry.vertex_stack);
+    assert!(geometry_view.corner_stack.layers.is_empty());
+
+    drop(filtered);
+    drop(view);
+    drop(mapped);
+    std::fs::remove_file(&path).expect("Expected to remove the file");
+}